@@ -0,0 +1,69 @@
+//! Measures the payload path between GStreamer buffers and OpenARK messages.
+//!
+//! Run with `cargo bench --package gsark-common --bench zero_copy`.
+
+use std::{hint::black_box, time::Instant};
+
+use gsark_common::buffer;
+use gst::Buffer;
+
+/// 4K MJPEG frame, with a generous compression ratio
+const FRAME_SIZE: usize = 3840 * 2160 / 2;
+
+const FRAMES: usize = 1_000;
+
+fn measure(name: &str, mut f: impl FnMut()) {
+    let instant = Instant::now();
+    for _ in 0..FRAMES {
+        f();
+    }
+    let elapsed = instant.elapsed() / FRAMES as u32;
+
+    println!("{name}: {elapsed:?}/frame");
+}
+
+/// Address of the buffer memory, as seen by the readers
+fn buffer_ptr(buffer: &Buffer) -> *const u8 {
+    buffer
+        .map_readable()
+        .expect("buffer should be readable")
+        .as_ptr()
+}
+
+fn main() {
+    gst::init().expect("GStreamer should be initialized");
+
+    let frame = Buffer::from_slice(vec![0u8; FRAME_SIZE]);
+
+    measure("send", || {
+        black_box(buffer::to_bytes(black_box(&frame)).unwrap());
+    });
+
+    let data = buffer::to_bytes(&frame).unwrap();
+    measure("recv", || {
+        black_box(buffer::from_bytes(black_box(data.clone())));
+    });
+
+    measure("roundtrip", || {
+        let data = buffer::to_bytes(black_box(&frame)).unwrap();
+        black_box(buffer::from_bytes(data));
+    });
+
+    // the payloads should share the frame memory, wherever it was allocated
+    let ptr = buffer_ptr(&frame);
+    assert_eq!(data.as_ptr(), ptr, "the sent payload should not be copied");
+
+    let received = buffer::from_bytes(data.clone());
+    assert_eq!(
+        buffer_ptr(&received),
+        ptr,
+        "the received buffer should not be copied",
+    );
+
+    let roundtrip = buffer::from_bytes(buffer::to_bytes(&received).unwrap());
+    assert_eq!(
+        buffer_ptr(&roundtrip),
+        ptr,
+        "the relayed buffer should not be copied",
+    );
+}
@@ -1,13 +1,26 @@
-use gst::glib::{
-    value::ToValue, ParamSpec, ParamSpecBoolean, ParamSpecBuilderExt, ParamSpecString, Value,
+use std::fmt;
+
+use gst::{
+    glib::{
+        self, value::ToValue, ParamSpec, ParamSpecBoolean, ParamSpecBoxed, ParamSpecBuilderExt,
+        ParamSpecDouble, ParamSpecEnum, ParamSpecInt, ParamSpecString, ParamSpecUInt,
+        ParamSpecUInt64, Value,
+    },
+    Structure,
 };
 use once_cell::sync::Lazy;
 
-use crate::{net::ChannelArgs, plugin::PluginImpl, value::set_value};
+use crate::{
+    net::ChannelArgs,
+    plugin::PluginImpl,
+    value::{set_secret_value, set_value},
+};
 
 /// Plugin property value storage
 #[derive(Clone, Debug)]
 pub struct Args {
+    client: ClientArgs,
+    dedicated_runtime: bool,
     model: String,
     otlp: bool,
 }
@@ -16,6 +29,8 @@ impl Default for Args {
     #[inline]
     fn default() -> Self {
         Args {
+            client: ClientArgs::default(),
+            dedicated_runtime: Self::default_dedicated_runtime(),
             model: Self::default_model(),
             otlp: Self::default_otlp(),
         }
@@ -23,6 +38,11 @@ impl Default for Args {
 }
 
 impl Args {
+    #[inline]
+    const fn default_dedicated_runtime() -> bool {
+        false
+    }
+
     #[inline]
     fn default_model() -> String {
         String::default()
@@ -40,6 +60,16 @@ impl ChannelArgs for Args {
         &self.model
     }
 
+    #[inline]
+    fn client(&self) -> &ClientArgs {
+        &self.client
+    }
+
+    #[inline]
+    fn dedicated_runtime(&self) -> bool {
+        self.dedicated_runtime
+    }
+
     #[inline]
     fn otlp(&self) -> bool {
         self.otlp
@@ -52,10 +82,12 @@ impl ChannelArgs for Args {
         PROPERTIES.as_ref()
     }
 
-    fn property(&self, _id: usize, pspec: &ParamSpec) -> Value {
+    fn property(&self, id: usize, pspec: &ParamSpec) -> Value {
         match pspec.name() {
+            "dedicated-runtime" => self.dedicated_runtime.to_value(),
             "model" => self.model.to_value(),
             "otlp" => self.otlp.to_value(),
+            name if ClientArgs::is_property(name) => self.client.property(id, pspec),
             _ => unimplemented!(),
         }
     }
@@ -63,14 +95,18 @@ impl ChannelArgs for Args {
     fn set_property(
         &mut self,
         plugin: &(impl ?Sized + PluginImpl),
-        _id: usize,
+        id: usize,
         value: &Value,
         pspec: &ParamSpec,
     ) {
         let name = pspec.name();
         match name {
+            "dedicated-runtime" => set_value(plugin, name, &mut self.dedicated_runtime, value),
             "model" => set_value(plugin, name, &mut self.model, value),
             "otlp" => set_value(plugin, name, &mut self.otlp, value),
+            name if ClientArgs::is_property(name) => {
+                self.client.set_property(plugin, id, value, pspec)
+            }
             _ => unimplemented!(),
         }
     }
@@ -78,7 +114,7 @@ impl ChannelArgs for Args {
 
 impl Args {
     pub fn as_params(&self) -> Vec<ParamSpec> {
-        vec![
+        let mut params = vec![
             ParamSpecString::builder("model")
                 .nick("Model")
                 .blurb("OpenARK model name")
@@ -89,6 +125,422 @@ impl Args {
                 .blurb("Whether to use OpenTelemetry")
                 .default_value(self.otlp)
                 .build(),
+            ParamSpecBoolean::builder("dedicated-runtime")
+                .nick("Dedicated Runtime")
+                .blurb(
+                    "Whether to run on a dedicated runtime, instead of the shared one, \
+                    chosen on the first start",
+                )
+                .default_value(self.dedicated_runtime)
+                .build(),
+        ];
+        params.extend(self.client.as_params());
+        params
+    }
+}
+
+/// OpenARK client property value storage
+///
+/// The unset fields fall back to the environment variables.
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct ClientArgs {
+    pub namespace: Option<String>,
+    pub nats_account: Option<String>,
+    pub nats_addrs: Option<String>,
+    pub nats_password_path: Option<String>,
+    pub s3_access_key: Option<String>,
+    pub s3_bucket: Option<String>,
+    pub s3_endpoint: Option<String>,
+    pub s3_region: Option<String>,
+    pub s3_secret_key: Option<String>,
+}
+
+impl ClientArgs {
+    /// Property names, nicks, blurbs and the matching `PipeClientArgs` flags
+    const FIELDS: &'static [(&'static str, &'static str, &'static str, &'static str)] = &[
+        (
+            "namespace",
+            "Namespace",
+            "OpenARK namespace of the model",
+            "--namespace",
+        ),
+        (
+            "nats-account",
+            "NATS Account",
+            "NATS account name",
+            "--nats-account",
+        ),
+        (
+            "nats-addrs",
+            "NATS Addresses",
+            "NATS server addresses, separated by commas",
+            "--nats-addrs",
+        ),
+        (
+            "nats-password-path",
+            "NATS Password Path",
+            "Path of the NATS password file",
+            "--nats-password-path",
+        ),
+        (
+            "s3-access-key",
+            "S3 Access Key",
+            "S3 access key ID",
+            "--access-key",
+        ),
+        ("s3-bucket", "S3 Bucket", "S3 bucket name", "--bucket-name"),
+        (
+            "s3-endpoint",
+            "S3 Endpoint",
+            "S3 endpoint URL",
+            "--s3-endpoint",
+        ),
+        ("s3-region", "S3 Region", "S3 region", "--region"),
+        (
+            "s3-secret-key",
+            "S3 Secret Key",
+            "S3 secret access key, which cannot be read back",
+            "--secret-key",
+        ),
+    ];
+
+    /// Properties holding credentials, which are never logged
+    const SECRETS: &'static [&'static str] = &["s3-access-key", "s3-secret-key"];
+
+    /// Properties which cannot be read back
+    const WRITE_ONLY: &'static [&'static str] = &["s3-secret-key"];
+
+    pub fn is_property(name: &str) -> bool {
+        Self::FIELDS.iter().any(|(field, ..)| *field == name)
+    }
+
+    fn field(&self, name: &str) -> &Option<String> {
+        match name {
+            "namespace" => &self.namespace,
+            "nats-account" => &self.nats_account,
+            "nats-addrs" => &self.nats_addrs,
+            "nats-password-path" => &self.nats_password_path,
+            "s3-access-key" => &self.s3_access_key,
+            "s3-bucket" => &self.s3_bucket,
+            "s3-endpoint" => &self.s3_endpoint,
+            "s3-region" => &self.s3_region,
+            "s3-secret-key" => &self.s3_secret_key,
+            _ => unimplemented!(),
+        }
+    }
+
+    fn field_mut(&mut self, name: &str) -> &mut Option<String> {
+        match name {
+            "namespace" => &mut self.namespace,
+            "nats-account" => &mut self.nats_account,
+            "nats-addrs" => &mut self.nats_addrs,
+            "nats-password-path" => &mut self.nats_password_path,
+            "s3-access-key" => &mut self.s3_access_key,
+            "s3-bucket" => &mut self.s3_bucket,
+            "s3-endpoint" => &mut self.s3_endpoint,
+            "s3-region" => &mut self.s3_region,
+            "s3-secret-key" => &mut self.s3_secret_key,
+            _ => unimplemented!(),
+        }
+    }
+
+    pub fn property(&self, _id: usize, pspec: &ParamSpec) -> Value {
+        let name = pspec.name();
+        if Self::WRITE_ONLY.contains(&name) {
+            return None::<String>.to_value();
+        }
+        self.field(name).to_value()
+    }
+
+    pub fn set_property(
+        &mut self,
+        plugin: &(impl ?Sized + PluginImpl),
+        _id: usize,
+        value: &Value,
+        pspec: &ParamSpec,
+    ) {
+        let name = pspec.name();
+        if Self::SECRETS.contains(&name) {
+            set_secret_value(plugin, name, self.field_mut(name), value)
+        } else {
+            set_value(plugin, name, self.field_mut(name), value)
+        }
+    }
+
+    pub fn as_params(&self) -> Vec<ParamSpec> {
+        Self::FIELDS
+            .iter()
+            .map(|&(name, nick, blurb, _)| {
+                let builder = ParamSpecString::builder(name)
+                    .nick(nick)
+                    .blurb(blurb)
+                    .default_value(None);
+                if Self::WRITE_ONLY.contains(&name) {
+                    builder.write_only().build()
+                } else {
+                    builder.build()
+                }
+            })
+            .collect()
+    }
+
+    /// Command line arguments of `PipeClientArgs`, only with the set fields
+    pub fn to_cli_args(&self) -> Vec<String> {
+        Self::FIELDS
+            .iter()
+            .filter_map(|&(name, _, _, flag)| {
+                self.field(name)
+                    .as_ref()
+                    .filter(|value| !value.is_empty())
+                    .map(|value| format!("{flag}={value}"))
+            })
+            .collect()
+    }
+}
+
+impl fmt::Debug for ClientArgs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut s = f.debug_struct("ClientArgs");
+        for &(name, ..) in Self::FIELDS {
+            match self.field(name) {
+                Some(_) if Self::SECRETS.contains(&name) => s.field(name, &"<redacted>"),
+                value => s.field(name, value),
+            };
+        }
+        s.finish()
+    }
+}
+
+/// Queue limit property value storage
+#[derive(Clone, Debug)]
+pub struct QueueLimits {
+    pub leaky: QueueLeaky,
+    pub max_size_buffers: u32,
+    pub max_size_bytes: u32,
+    pub max_size_time: u64,
+}
+
+impl Default for QueueLimits {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            leaky: QueueLeaky::default(),
+            max_size_buffers: 4,
+            max_size_bytes: 0,
+            max_size_time: 0,
+        }
+    }
+}
+
+impl QueueLimits {
+    pub fn is_property(name: &str) -> bool {
+        matches!(
+            name,
+            "leaky" | "max-size-buffers" | "max-size-bytes" | "max-size-time"
+        )
+    }
+
+    pub fn property(&self, _id: usize, pspec: &ParamSpec) -> Value {
+        match pspec.name() {
+            "leaky" => self.leaky.to_value(),
+            "max-size-buffers" => self.max_size_buffers.to_value(),
+            "max-size-bytes" => self.max_size_bytes.to_value(),
+            "max-size-time" => self.max_size_time.to_value(),
+            _ => unimplemented!(),
+        }
+    }
+
+    pub fn set_property(
+        &mut self,
+        plugin: &(impl ?Sized + PluginImpl),
+        _id: usize,
+        value: &Value,
+        pspec: &ParamSpec,
+    ) {
+        let name = pspec.name();
+        match name {
+            "leaky" => set_value(plugin, name, &mut self.leaky, value),
+            "max-size-buffers" => set_value(plugin, name, &mut self.max_size_buffers, value),
+            "max-size-bytes" => set_value(plugin, name, &mut self.max_size_bytes, value),
+            "max-size-time" => set_value(plugin, name, &mut self.max_size_time, value),
+            _ => unimplemented!(),
+        }
+    }
+
+    pub fn as_params(&self) -> Vec<ParamSpec> {
+        vec![
+            ParamSpecEnum::builder_with_default("leaky", self.leaky)
+                .nick("Leaky")
+                .blurb("Where the queue leaks, if at all")
+                .build(),
+            ParamSpecUInt::builder("max-size-buffers")
+                .nick("Max. size (buffers)")
+                .blurb("Max. number of messages in the queue (0=disable)")
+                .default_value(self.max_size_buffers)
+                .build(),
+            ParamSpecUInt::builder("max-size-bytes")
+                .nick("Max. size (bytes)")
+                .blurb("Max. amount of payload bytes in the queue (0=disable)")
+                .default_value(self.max_size_bytes)
+                .build(),
+            ParamSpecUInt64::builder("max-size-time")
+                .nick("Max. size (ns)")
+                .blurb("Max. amount of data in the queue, in ns (0=disable)")
+                .default_value(self.max_size_time)
+                .build(),
+        ]
+    }
+}
+
+/// Queue leaking policy, following the `queue` element
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, glib::Enum)]
+#[repr(u32)]
+#[enum_type(name = "GsArkQueueLeaky")]
+pub enum QueueLeaky {
+    #[default]
+    #[enum_value(name = "Not Leaky", nick = "no")]
+    No,
+    #[enum_value(name = "Leaky on upstream (new buffers)", nick = "upstream")]
+    Upstream,
+    #[enum_value(name = "Leaky on downstream (old buffers)", nick = "downstream")]
+    Downstream,
+}
+
+/// Reconnect policy property value storage
+#[derive(Clone, Debug)]
+pub struct ReconnectArgs {
+    pub jitter: f64,
+    pub max_backoff: u32,
+    pub max_retries: i32,
+    pub min_backoff: u32,
+}
+
+impl Default for ReconnectArgs {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            jitter: 0.1,
+            max_backoff: 30_000,
+            max_retries: -1,
+            min_backoff: 100,
+        }
+    }
+}
+
+impl ReconnectArgs {
+    pub fn is_property(name: &str) -> bool {
+        matches!(
+            name,
+            "reconnect-jitter"
+                | "reconnect-max-backoff"
+                | "reconnect-max-retries"
+                | "reconnect-min-backoff"
+        )
+    }
+
+    pub fn property(&self, _id: usize, pspec: &ParamSpec) -> Value {
+        match pspec.name() {
+            "reconnect-jitter" => self.jitter.to_value(),
+            "reconnect-max-backoff" => self.max_backoff.to_value(),
+            "reconnect-max-retries" => self.max_retries.to_value(),
+            "reconnect-min-backoff" => self.min_backoff.to_value(),
+            _ => unimplemented!(),
+        }
+    }
+
+    pub fn set_property(
+        &mut self,
+        plugin: &(impl ?Sized + PluginImpl),
+        _id: usize,
+        value: &Value,
+        pspec: &ParamSpec,
+    ) {
+        let name = pspec.name();
+        match name {
+            "reconnect-jitter" => set_value(plugin, name, &mut self.jitter, value),
+            "reconnect-max-backoff" => set_value(plugin, name, &mut self.max_backoff, value),
+            "reconnect-max-retries" => set_value(plugin, name, &mut self.max_retries, value),
+            "reconnect-min-backoff" => set_value(plugin, name, &mut self.min_backoff, value),
+            _ => unimplemented!(),
+        }
+    }
+
+    pub fn as_params(&self) -> Vec<ParamSpec> {
+        vec![
+            ParamSpecDouble::builder("reconnect-jitter")
+                .nick("Reconnect Jitter")
+                .blurb("Random fraction added to or removed from each backoff")
+                .minimum(0.0)
+                .maximum(1.0)
+                .default_value(self.jitter)
+                .build(),
+            ParamSpecUInt::builder("reconnect-max-backoff")
+                .nick("Reconnect Max. Backoff")
+                .blurb("Max. delay between the reconnect attempts, in ms")
+                .default_value(self.max_backoff)
+                .build(),
+            ParamSpecInt::builder("reconnect-max-retries")
+                .nick("Reconnect Max. Retries")
+                .blurb("Max. number of consecutive reconnect attempts (-1=unlimited, 0=disable)")
+                .minimum(-1)
+                .default_value(self.max_retries)
+                .build(),
+            ParamSpecUInt::builder("reconnect-min-backoff")
+                .nick("Reconnect Min. Backoff")
+                .blurb("Delay before the first reconnect attempt, doubled on each failure, in ms")
+                .default_value(self.min_backoff)
+                .build(),
+        ]
+    }
+}
+
+/// Stats property value storage
+///
+/// The read-only `stats` property itself is served by the element,
+/// from the stats of its channel.
+#[derive(Clone, Debug, Default)]
+pub struct StatsArgs {
+    pub interval: u32,
+}
+
+impl StatsArgs {
+    pub fn is_property(name: &str) -> bool {
+        matches!(name, "stats" | "stats-interval")
+    }
+
+    pub fn property(&self, _id: usize, pspec: &ParamSpec) -> Value {
+        match pspec.name() {
+            "stats-interval" => self.interval.to_value(),
+            _ => unimplemented!(),
+        }
+    }
+
+    pub fn set_property(
+        &mut self,
+        plugin: &(impl ?Sized + PluginImpl),
+        _id: usize,
+        value: &Value,
+        pspec: &ParamSpec,
+    ) {
+        let name = pspec.name();
+        match name {
+            "stats-interval" => set_value(plugin, name, &mut self.interval, value),
+            _ => unimplemented!(),
+        }
+    }
+
+    pub fn as_params(&self) -> Vec<ParamSpec> {
+        vec![
+            ParamSpecBoxed::builder::<Structure>("stats")
+                .nick("Stats")
+                .blurb("Message statistics of the channel")
+                .read_only()
+                .build(),
+            ParamSpecUInt::builder("stats-interval")
+                .nick("Stats Interval")
+                .blurb("Interval of posting the stats as element messages, in ms (0=disable)")
+                .default_value(self.interval)
+                .build(),
         ]
     }
 }
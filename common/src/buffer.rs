@@ -0,0 +1,187 @@
+use std::collections::VecDeque;
+
+use bytes::Bytes;
+use dash_pipe_provider::DynValue;
+use gst::{Buffer, BufferFlags, BufferRef, ClockTime, BUFFER_OFFSET_NONE};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Wrap the buffer data as a payload, without copying.
+///
+/// The buffer is kept mapped until the payload is dropped.
+/// Returns `None` if the buffer is not readable.
+pub fn to_bytes(buffer: &Buffer) -> Option<Bytes> {
+    buffer
+        .clone()
+        .into_mapped_buffer_readable()
+        .map(Bytes::from_owner)
+        .ok()
+}
+
+/// Wrap the payload as a buffer memory, without copying.
+#[inline]
+pub fn from_bytes(data: Bytes) -> Buffer {
+    Buffer::from_slice(data)
+}
+
+/// Buffer timestamps and flags, carried along with the payloads
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BufferInfo {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pts: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dts: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub flags: Vec<String>,
+}
+
+impl BufferInfo {
+    /// Field name of the buffer info in the message value
+    pub const KEY: &'static str = "buffer";
+
+    const FLAGS: &'static [(BufferFlags, &'static str)] = &[
+        (BufferFlags::DELTA_UNIT, "delta-unit"),
+        (BufferFlags::DISCONT, "discont"),
+        (BufferFlags::HEADER, "header"),
+    ];
+
+    pub fn from_buffer(buffer: &BufferRef) -> Self {
+        let buffer_flags = buffer.flags();
+
+        Self {
+            pts: buffer.pts().map(ClockTime::nseconds),
+            dts: buffer.dts().map(ClockTime::nseconds),
+            duration: buffer.duration().map(ClockTime::nseconds),
+            offset: Some(buffer.offset()).filter(|&offset| offset != BUFFER_OFFSET_NONE),
+            flags: Self::FLAGS
+                .iter()
+                .filter(|(flag, _)| buffer_flags.contains(*flag))
+                .map(|(_, name)| name.to_string())
+                .collect(),
+        }
+    }
+
+    pub fn from_value(value: &DynValue) -> Option<Self> {
+        value
+            .get(Self::KEY)
+            .and_then(|info| Self::deserialize(info).ok())
+    }
+
+    pub fn insert_into(&self, value: &mut DynValue) {
+        if let Some(value) = value.as_object_mut() {
+            let info = ::serde_json::to_value(self).expect("buffer info should be serializable");
+            value.insert(Self::KEY.into(), info);
+        }
+    }
+
+    /// Derive the info of the `index`-th of `count` payloads sharing this message info
+    ///
+    /// The message duration is split evenly among the payloads.
+    /// Without a duration, only the first payload keeps the timestamps.
+    pub fn interpolate(&self, index: usize, count: usize) -> Self {
+        if count <= 1 {
+            return self.clone();
+        }
+
+        let step = self.duration.map(|duration| duration / count as u64);
+        let shift = |time: Option<u64>| match index {
+            0 => time,
+            _ => Some(time? + step? * index as u64),
+        };
+        Self {
+            pts: shift(self.pts),
+            dts: shift(self.dts),
+            duration: step,
+            offset: self.offset.map(|offset| offset + index as u64),
+            // the discontinuity belongs to the first payload only
+            flags: self
+                .flags
+                .iter()
+                .filter(|&flag| index == 0 || flag != "discont")
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// Mark the buffer as a discontinuity.
+    pub fn set_discont(&mut self) {
+        if !self.flags.iter().any(|flag| flag == "discont") {
+            self.flags.push("discont".into());
+        }
+    }
+
+    pub fn apply(&self, buffer: &mut BufferRef) {
+        buffer.set_pts(self.pts.map(ClockTime::from_nseconds));
+        buffer.set_dts(self.dts.map(ClockTime::from_nseconds));
+        buffer.set_duration(self.duration.map(ClockTime::from_nseconds));
+        buffer.set_offset(self.offset.unwrap_or(BUFFER_OFFSET_NONE));
+
+        let buffer_flags = Self::FLAGS
+            .iter()
+            .filter(|(_, name)| self.flags.iter().any(|flag| flag == name))
+            .fold(BufferFlags::empty(), |flags, (flag, _)| flags | *flag);
+        buffer.set_flags(buffer_flags);
+    }
+}
+
+/// Rebases the received timestamps onto the running time of the receiving pipeline
+#[derive(Debug, Default)]
+pub struct TimestampRebase {
+    base: Option<(u64, u64)>,
+}
+
+impl TimestampRebase {
+    pub fn apply(
+        &mut self,
+        info: &mut BufferInfo,
+        running_time: impl FnOnce() -> Option<ClockTime>,
+    ) {
+        let (from, to) = match self.base {
+            Some(base) => base,
+            None => match info.dts.or(info.pts) {
+                // the stream starts at 0 if the running time is unknown, e.g. on prerolling
+                Some(from) => {
+                    let to = running_time().unwrap_or(ClockTime::ZERO);
+                    *self.base.insert((from, to.nseconds()))
+                }
+                None => return,
+            },
+        };
+
+        let rebase = |time: u64| (time as i128 - from as i128 + to as i128).max(0) as u64;
+        info.pts = info.pts.map(rebase);
+        info.dts = info.dts.map(rebase);
+    }
+
+    #[inline]
+    pub fn reset(&mut self) {
+        self.base = None;
+    }
+}
+
+/// Sizes of the recently received payloads, to size the buffer pools
+#[derive(Debug, Default)]
+pub struct RecentSizes {
+    sizes: VecDeque<usize>,
+}
+
+impl RecentSizes {
+    const CAPACITY: usize = 16;
+
+    pub fn push(&mut self, size: usize) {
+        if self.sizes.len() == Self::CAPACITY {
+            self.sizes.pop_front();
+        }
+        self.sizes.push_back(size);
+    }
+
+    /// Returns the largest recent size, or 0 if none.
+    pub fn max(&self) -> usize {
+        self.sizes.iter().copied().max().unwrap_or_default()
+    }
+}
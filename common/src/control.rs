@@ -0,0 +1,42 @@
+use dash_pipe_provider::{DynValue, PipeMessage};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Control message, sent without payloads along with the data messages
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum ControlMessage {
+    /// The upstream pipeline reached the end of stream
+    Eos,
+}
+
+impl ControlMessage {
+    /// Field name of the control message in the message value
+    pub const KEY: &'static str = "control";
+
+    pub fn from_message(message: &PipeMessage) -> Option<Self> {
+        if message
+            .payloads
+            .iter()
+            .any(|payload| payload.value().is_some())
+        {
+            return None;
+        }
+
+        message
+            .value
+            .get(Self::KEY)
+            .and_then(|control| Self::deserialize(control).ok())
+    }
+
+    pub fn to_message(self) -> PipeMessage {
+        let mut value = DynValue::Object(Default::default());
+        if let Some(value) = value.as_object_mut() {
+            let control =
+                ::serde_json::to_value(self).expect("control message should be serializable");
+            value.insert(Self::KEY.into(), control);
+        }
+
+        PipeMessage::with_payloads(vec![], value)
+    }
+}
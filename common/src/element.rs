@@ -1,4 +1,4 @@
-use gst::{Caps, PadDirection, PadPresence, PadTemplate};
+use gst::{glib::Type, Caps, PadDirection, PadPresence, PadTemplate};
 
 pub fn sink_dynamic() -> PadTemplate {
     PadTemplate::new(
@@ -19,3 +19,35 @@ pub fn src_dynamic() -> PadTemplate {
     )
     .unwrap()
 }
+
+pub fn src_request(pad_type: Type) -> PadTemplate {
+    PadTemplate::with_gtype(
+        "src_%s",
+        PadDirection::Src,
+        PadPresence::Request,
+        &Caps::new_any(),
+        pad_type,
+    )
+    .unwrap()
+}
+
+pub fn sink_request(pad_type: Type) -> PadTemplate {
+    PadTemplate::with_gtype(
+        "sink_%u",
+        PadDirection::Sink,
+        PadPresence::Request,
+        &Caps::new_any(),
+        pad_type,
+    )
+    .unwrap()
+}
+
+pub fn src_sometimes() -> PadTemplate {
+    PadTemplate::new(
+        "src_%s",
+        PadDirection::Src,
+        PadPresence::Sometimes,
+        &Caps::new_any(),
+    )
+    .unwrap()
+}
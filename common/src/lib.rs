@@ -1,6 +1,13 @@
 pub mod args;
+pub mod buffer;
+pub mod control;
 pub mod element;
+pub mod media;
+pub mod meta;
 pub mod net;
+pub mod payload;
 pub mod plugin;
+pub mod runtime;
+pub mod stream;
 pub mod sync;
 pub mod value;
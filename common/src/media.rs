@@ -0,0 +1,124 @@
+use gst::{Caps, CapsRef, StructureRef};
+
+/// Media type registry entry, mapping GStreamer caps into file extensions and MIME types
+#[derive(Debug)]
+pub struct MediaType {
+    /// Caps structure name
+    pub name: &'static str,
+    /// File extension, including the leading dot
+    pub ext: &'static str,
+    /// MIME type
+    pub mime: &'static str,
+    filter: Option<fn(&StructureRef) -> bool>,
+}
+
+impl MediaType {
+    /// Fallback media type of unknown caps
+    pub const FALLBACK: Self = Self::new(
+        "application/octet-stream",
+        ".bin",
+        "application/octet-stream",
+    );
+
+    const fn new(name: &'static str, ext: &'static str, mime: &'static str) -> Self {
+        Self {
+            name,
+            ext,
+            mime,
+            filter: None,
+        }
+    }
+
+    const fn with_filter(self, filter: fn(&StructureRef) -> bool) -> Self {
+        Self {
+            filter: Some(filter),
+            ..self
+        }
+    }
+
+    pub fn from_caps(caps: &CapsRef) -> &'static Self {
+        caps.structure(0)
+            .and_then(|s| {
+                MEDIA_TYPES.iter().find(|media| {
+                    s.name().as_str() == media.name
+                        && media.filter.map(|filter| filter(s)).unwrap_or(true)
+                })
+            })
+            .unwrap_or(&Self::FALLBACK)
+    }
+
+    pub fn from_ext(ext: &str) -> Option<&'static Self> {
+        MEDIA_TYPES
+            .iter()
+            .find(|media| media.ext.eq_ignore_ascii_case(ext))
+    }
+
+    pub fn from_key(key: &str) -> Option<&'static Self> {
+        key.rfind('.')
+            .and_then(|index| Self::from_ext(&key[index..]))
+    }
+
+    pub fn from_mime(mime: &str) -> Option<&'static Self> {
+        if mime == Self::FALLBACK.mime {
+            return Some(&Self::FALLBACK);
+        }
+        MEDIA_TYPES.iter().find(|media| media.mime == mime)
+    }
+
+    /// Build caps of this media type, which may be unfixed for the raw formats
+    pub fn to_caps(&self) -> Caps {
+        let builder = Caps::builder(self.name);
+        match self.ext {
+            ".aac" => builder.field("mpegversion", 4i32).build(),
+            ".h264" | ".h265" => builder
+                .field("stream-format", "byte-stream")
+                .field("alignment", "au")
+                .build(),
+            ".mp3" => builder
+                .field("mpegversion", 1i32)
+                .field("layer", 3i32)
+                .build(),
+            ".mp4" => builder.field("variant", "iso").build(),
+            ".txt" => builder.field("format", "utf8").build(),
+            _ => builder.build(),
+        }
+    }
+}
+
+static MEDIA_TYPES: &[MediaType] = &[
+    // application
+    MediaType::new("application/ogg", ".ogg", "application/ogg"),
+    MediaType::new("application/x-json", ".json", "application/json"),
+    MediaType::new("application/x-subtitle", ".srt", "application/x-subrip"),
+    // audio
+    MediaType::new("audio/mpeg", ".mp3", "audio/mpeg")
+        .with_filter(|s| s.get::<i32>("mpegversion").ok() == Some(1)),
+    MediaType::new("audio/mpeg", ".aac", "audio/aac"),
+    MediaType::new("audio/x-flac", ".flac", "audio/flac"),
+    MediaType::new("audio/x-opus", ".opus", "audio/opus"),
+    MediaType::new("audio/x-raw", ".pcm", "audio/pcm"),
+    MediaType::new("audio/x-wav", ".wav", "audio/wav"),
+    // image
+    MediaType::new("image/bmp", ".bmp", "image/bmp"),
+    MediaType::new("image/gif", ".gif", "image/gif"),
+    MediaType::new("image/jpeg", ".jpg", "image/jpeg"),
+    MediaType::new("image/png", ".png", "image/png"),
+    MediaType::new("image/tiff", ".tiff", "image/tiff"),
+    MediaType::new("image/webp", ".webp", "image/webp"),
+    // text
+    MediaType::new("text/x-raw", ".txt", "text/plain"),
+    // video
+    MediaType::new("video/mpegts", ".ts", "video/mp2t"),
+    MediaType::new("video/quicktime", ".mp4", "video/mp4")
+        .with_filter(|s| s.get::<&str>("variant").ok() == Some("iso")),
+    MediaType::new("video/quicktime", ".mov", "video/quicktime"),
+    MediaType::new("video/webm", ".webm", "video/webm"),
+    MediaType::new("video/x-av1", ".av1", "video/av1"),
+    MediaType::new("video/x-h264", ".h264", "video/h264"),
+    MediaType::new("video/x-h265", ".h265", "video/h265"),
+    MediaType::new("video/x-matroska", ".mkv", "video/x-matroska"),
+    MediaType::new("video/x-msvideo", ".avi", "video/x-msvideo"),
+    MediaType::new("video/x-raw", ".raw", "video/raw"),
+    MediaType::new("video/x-vp8", ".vp8", "video/vp8"),
+    MediaType::new("video/x-vp9", ".vp9", "video/vp9"),
+];
@@ -0,0 +1,128 @@
+use std::sync::Once;
+
+use dash_pipe_provider::{DynValue, PipeMessage};
+use gst::{meta::CustomMeta, BufferRef};
+
+use crate::{buffer::BufferInfo, control::ControlMessage, net::BatchItem, stream::StreamInfo};
+
+/// Custom meta carrying an OpenARK message along with the buffer
+///
+/// It is registered as a [`CustomMeta`] so that any element, including the
+/// Python ones, can read its structure with the following fields:
+///
+/// * `key`: the payload key
+/// * `model`: the OpenARK model name
+/// * `value`: the message value, serialized in JSON
+/// * `headers`: the other message fields (e.g. reply inbox, trace context), serialized in JSON
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ArkMessageMeta {
+    pub key: String,
+    pub model: String,
+    pub value: DynValue,
+    pub headers: DynValue,
+}
+
+impl ArkMessageMeta {
+    pub const NAME: &'static str = "ArkMessageMeta";
+
+    /// Fields of the message value managed by the elements, which are never relayed
+    pub const RESERVED_KEYS: &'static [&'static str] = &[
+        BatchItem::KEY,
+        BufferInfo::KEY,
+        ControlMessage::KEY,
+        "data",
+        StreamInfo::KEY,
+    ];
+
+    pub fn register() {
+        static REGISTER: Once = Once::new();
+
+        REGISTER.call_once(|| {
+            CustomMeta::register(Self::NAME, &[]);
+        });
+    }
+
+    pub fn from_message(model: &str, key: &str, message: &PipeMessage) -> Self {
+        let mut envelope = message.clone();
+        envelope.payloads.clear();
+        envelope.value = DynValue::Null;
+
+        let mut headers = ::serde_json::to_value(&envelope).unwrap_or_default();
+        if let Some(headers) = headers.as_object_mut() {
+            headers.remove("payloads");
+            headers.remove("value");
+        }
+
+        Self {
+            key: key.into(),
+            model: model.into(),
+            value: message.value.clone(),
+            headers,
+        }
+    }
+
+    pub fn from_buffer(buffer: &BufferRef) -> Option<Self> {
+        let meta = CustomMeta::from_buffer(buffer, Self::NAME).ok()?;
+        let s = meta.structure();
+
+        let json = |name| {
+            s.get::<&str>(name)
+                .ok()
+                .and_then(|value| ::serde_json::from_str(value).ok())
+                .unwrap_or_default()
+        };
+
+        Some(Self {
+            key: s.get("key").unwrap_or_default(),
+            model: s.get("model").unwrap_or_default(),
+            value: json("value"),
+            headers: json("headers"),
+        })
+    }
+
+    /// Message value to be relayed, without the fields managed by the elements
+    pub fn into_relayed_value(self) -> DynValue {
+        let mut value = self.value;
+        if let Some(fields) = value.as_object_mut() {
+            for key in Self::RESERVED_KEYS {
+                fields.remove(*key);
+            }
+        }
+        value
+    }
+
+    pub fn add(&self, buffer: &mut BufferRef) {
+        Self::register();
+
+        if let Ok(mut meta) = CustomMeta::add(buffer, Self::NAME) {
+            let s = meta.mut_structure();
+            s.set("key", self.key.as_str());
+            s.set("model", self.model.as_str());
+            s.set("value", self.value.to_string());
+            s.set("headers", self.headers.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn relayed_value_drops_the_upstream_fields() {
+        let value = json!({
+            "batch": [{ "key": "000000.jpg" }, { "key": "000001.jpg" }],
+            "buffer": { "pts": 42 },
+            "control": "eos",
+            "data": "@data:image,000001.jpg",
+            "label": "cam-1",
+            "stream": { "streamId": "upstream" },
+        });
+        let message = PipeMessage::with_payloads(vec![], value);
+
+        let meta = ArkMessageMeta::from_message("model", "000001.jpg", &message);
+        assert_eq!(meta.into_relayed_value(), json!({ "label": "cam-1" }));
+    }
+}
@@ -0,0 +1,100 @@
+use dash_pipe_provider::{DynValue, PipeMessage, PipePayload};
+use serde::{Deserialize, Serialize};
+
+use crate::{buffer::BufferInfo, stream::StreamInfo};
+
+/// Per-payload entry of a batched message value
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchItem {
+    pub key: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub buffer: Option<BufferInfo>,
+}
+
+impl BatchItem {
+    /// Field name of the batch items in the message value
+    pub const KEY: &'static str = "batch";
+
+    /// Parse the batch items in the payload order, if any
+    pub fn from_value(value: &DynValue) -> Option<Vec<Self>> {
+        value
+            .get(Self::KEY)
+            .and_then(|items| Vec::<Self>::deserialize(items).ok())
+    }
+}
+
+/// Buffers accumulated into a multi-payload message
+#[derive(Debug, Default)]
+pub(super) struct Batch {
+    items: Vec<BatchItem>,
+    payloads: Vec<PipePayload>,
+    value: Option<DynValue>,
+}
+
+impl Batch {
+    pub(super) fn push(&mut self, payload: PipePayload, mut value: DynValue) {
+        let item = BatchItem {
+            key: payload.key().into(),
+            buffer: BufferInfo::from_value(&value),
+        };
+
+        match &mut self.value {
+            // the message value follows the first payload
+            None => self.value = Some(value),
+            Some(first) => {
+                // but the stream changes should not be missed
+                if let Some(stream) = value
+                    .as_object_mut()
+                    .and_then(|value| value.remove(StreamInfo::KEY))
+                {
+                    if let Some(first) = first.as_object_mut() {
+                        first.insert(StreamInfo::KEY.into(), stream);
+                    }
+                }
+            }
+        }
+        self.items.push(item);
+        self.payloads.push(payload);
+    }
+
+    #[inline]
+    pub(super) fn is_empty(&self) -> bool {
+        self.payloads.is_empty()
+    }
+
+    /// Whether the batch reached the max. number of payloads or duration in ns
+    pub(super) fn is_full(&self, size: u32, duration: u64) -> bool {
+        let timestamp =
+            |item: &BatchItem| item.buffer.as_ref().and_then(|info| info.dts.or(info.pts));
+
+        self.payloads.len() >= size as usize
+            || (duration > 0
+                && matches!(
+                    (
+                        self.items.first().and_then(timestamp),
+                        self.items.last().and_then(timestamp),
+                    ),
+                    (Some(first), Some(last)) if last.saturating_sub(first) >= duration
+                ))
+    }
+
+    /// Take the accumulated buffers as a message, if any
+    pub(super) fn take(&mut self) -> Option<PipeMessage> {
+        let Self {
+            items,
+            payloads,
+            value,
+        } = ::core::mem::take(self);
+        let mut value = value?;
+
+        if items.len() > 1 {
+            if let Some(value) = value.as_object_mut() {
+                let items =
+                    ::serde_json::to_value(items).expect("batch items should be serializable");
+                value.insert(BatchItem::KEY.into(), items);
+            }
+        }
+        Some(PipeMessage::with_payloads(payloads, value))
+    }
+}
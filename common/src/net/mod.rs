@@ -1,40 +1,153 @@
+mod batch;
+mod pool;
+mod queue;
+mod reconnect;
 mod recv;
 mod send;
+mod stats;
 
-use std::future::Future;
+use std::{
+    collections::VecDeque,
+    future::Future,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 
 use anyhow::{anyhow, Result};
 use ark_core::tracer;
 use async_trait::async_trait;
-use bytes::Bytes;
 use clap::Parser;
-use dash_openapi::image::Image;
-use dash_pipe_provider::{Name, PipeClient, PipeClientArgs, PipeMessage, PipePayload};
+use dash_pipe_provider::{DynValue, Name, PipeClient, PipeClientArgs, PipeMessage, PipePayload};
+use glob::Pattern;
 use gst::{
-    debug, error, error_msg,
-    glib::{subclass::types::ObjectSubclassExt, ParamSpec, Value},
-    info, Buffer, BufferRef, CoreError, ErrorMessage, FlowError, FlowSuccess,
+    debug, error, error_msg, event,
+    glib::{
+        subclass::types::{ObjectSubclass, ObjectSubclassExt, ObjectSubclassIsExt},
+        ParamSpec, Value, WeakRef,
+    },
+    info, message,
+    prelude::{
+        BufferPoolExt, BufferPoolExtManual, Cast, ElementExt, ElementExtManual, IsA, ObjectExt,
+        PadExt, StaticType,
+    },
+    warning, Buffer, BufferCopyFlags, BufferFlags, BufferList, BufferPool, BufferRef, Caps,
+    CapsRef, CoreError, Element, ErrorMessage, Event, FlowError, FlowSuccess, Pad, Structure,
+};
+use gst_video::gst_base::{
+    prelude::{BaseSrcExt, BaseSrcExtManual},
+    subclass::base_src::CreateSuccess,
+    BaseSrc,
 };
-use gst_video::gst_base::subclass::base_src::CreateSuccess;
 use schemars::JsonSchema;
 use tokio::{
     join,
-    sync::{MappedMutexGuard, Mutex, RwLock, RwLockReadGuard},
+    runtime::Runtime,
+    select,
+    sync::{MappedMutexGuard, Mutex, Notify, RwLock, RwLockReadGuard},
+    task::JoinHandle,
 };
+use tokio_util::sync::CancellationToken;
 
 use crate::{
+    args::{ClientArgs, QueueLimits, ReconnectArgs},
+    buffer::{BufferInfo, RecentSizes, TimestampRebase},
+    control::ControlMessage,
+    meta::ArkMessageMeta,
+    payload::{self, PayloadKind, PayloadMode},
     plugin::{base::ArkSubclass, PluginImpl},
+    stream::StreamInfo,
     sync,
 };
 
+use self::{batch::Batch, reconnect::Backoff};
+
+pub use self::{batch::BatchItem, pool::ClientPool, stats::Stats};
+
 pub trait ChannelArgs
 where
     Self: Send + Sync + Default,
 {
     fn model(&self) -> &String;
 
+    fn client(&self) -> &ClientArgs;
+
     fn otlp(&self) -> bool;
 
+    /// Limits of the message queue.
+    #[inline]
+    fn queue(&self) -> QueueLimits {
+        QueueLimits::default()
+    }
+
+    /// Whether to run on a dedicated runtime, instead of the shared one.
+    #[inline]
+    fn dedicated_runtime(&self) -> bool {
+        false
+    }
+
+    /// Reconnect policy of the subscriber and publisher.
+    #[inline]
+    fn reconnect(&self) -> ReconnectArgs {
+        ReconnectArgs::default()
+    }
+
+    /// Interval of posting the stats messages in ms, or 0 if disabled.
+    #[inline]
+    fn stats_interval(&self) -> u32 {
+        0
+    }
+
+    /// Max. number of buffers sent in a message.
+    #[inline]
+    fn batch_size(&self) -> u32 {
+        1
+    }
+
+    /// Max. timestamp span of buffers sent in a message in ns, or 0 if disabled.
+    #[inline]
+    fn batch_duration(&self) -> u64 {
+        0
+    }
+
+    /// Caps forced on the received payloads, instead of inferring them.
+    #[inline]
+    fn caps(&self) -> Option<Caps> {
+        None
+    }
+
+    /// Static value merged into every sent message.
+    #[inline]
+    fn value(&self) -> Option<&DynValue> {
+        None
+    }
+
+    /// Pattern of the payload keys to be received.
+    #[inline]
+    fn payload_key(&self) -> Option<&Pattern> {
+        None
+    }
+
+    /// How to produce the selected payloads.
+    #[inline]
+    fn payload_mode(&self) -> PayloadMode {
+        PayloadMode::default()
+    }
+
+    /// Whether to ignore the received end-of-stream control messages.
+    #[inline]
+    fn ignore_eos(&self) -> bool {
+        false
+    }
+
+    /// Whether to rebase the received timestamps onto the running time.
+    #[inline]
+    fn rebase_timestamps(&self) -> bool {
+        false
+    }
+
     /// Properties installed for this type.
     fn properties() -> &'static [ParamSpec];
 
@@ -65,75 +178,404 @@ pub trait ChannelSubclassExt
 where
     Self: ChannelSubclass + PluginImpl,
 {
-    async fn start(&self) -> Result<(), ErrorMessage> {
+    async fn start(&self) -> Result<(), ErrorMessage>
+    where
+        <Self as ObjectSubclass>::Type: IsA<Element>,
+    {
+        let (model, stats_interval) = {
+            let args = self.args().read().await;
+            (args.model().clone(), args.stats_interval())
+        };
+
+        self.start_channel(self.channel(), model).await?;
+
+        let element = self.obj().upcast_ref::<Element>().downgrade();
+        self.channel()
+            .start_stats(self.runtime(), element, stats_interval)
+            .await;
+
+        info!(
+            self.cat(),
+            imp: self,
+            "Started",
+        );
+        Ok(())
+    }
+
+    /// Starts the given channel of this element bound to the `model`, e.g. of a request pad.
+    async fn start_channel(&self, channel: &Channel, model: String) -> Result<(), ErrorMessage> {
         let args = self.args().read().await;
-        let model = args.model().clone();
+        let client = args.client().clone();
         let otlp = args.otlp();
+        let queue = args.queue();
+        let reconnect = args.reconnect();
         drop(args);
 
         ChannelBuilder::new(model)
+            .client(client)
             .otlp(otlp)
-            .build(self.channel())
-            .await?;
+            .queue(queue)
+            .reconnect(reconnect)
+            .build(channel)
+            .await
+    }
+
+    /// Switches to the current `model` in the background, if started.
+    ///
+    /// Otherwise the model is picked up on starting, e.g. when set in NULL.
+    fn spawn_switch_model(&self)
+    where
+        <Self as ObjectSubclass>::Type: IsA<Element>,
+    {
+        if !self.channel().is_started() {
+            return;
+        }
+
+        // the element may be disposed in the meantime
+        let element = self.obj().downgrade();
+        self.runtime().spawn(async move {
+            if let Some(element) = element.upgrade() {
+                element.imp().switch_model().await
+            }
+        });
+    }
+
+    /// Switches to the current `model`, if started.
+    async fn switch_model(&self)
+    where
+        <Self as ObjectSubclass>::Type: IsA<Element>,
+    {
+        let model = self.args().read().await.model().clone();
+        self.switch_channel_model(self.channel(), model).await
+    }
+
+    /// Switches the given channel of this element to the `model`, if started.
+    ///
+    /// The next buffer is marked as DISCONT, and an `ark-model-changed`
+    /// element message is posted on the bus.
+    async fn switch_channel_model(&self, channel: &Channel, model: String)
+    where
+        <Self as ObjectSubclass>::Type: IsA<Element>,
+    {
+        let old_model = match channel.switch_model(model.clone()).await {
+            Some(old_model) => old_model,
+            None => return,
+        };
 
         info!(
             self.cat(),
             imp: self,
-            "Started",
+            "Switched model from {old_model} to {model}",
         );
-        Ok(())
+
+        let element = self.obj();
+        let structure = Structure::builder("ark-model-changed")
+            .field("old-model", old_model)
+            .field("model", model)
+            .build();
+        let message = message::Element::builder(structure)
+            .src(element.upcast_ref::<Element>())
+            .build();
+        // the element may not be in a bin
+        let _ = element.post_message(message);
     }
 
+    #[inline]
     async fn start_send(&self) -> Result<(), ErrorMessage> {
-        match self.channel().init_send(self).await {
-            Ok(Some(_)) => Ok(()),
-            Ok(None) => Err(error_msg!(
-                CoreError::Failed,
-                ["OpenARK client is not inited!"]
-            )),
-            Err(error) => Err(error_msg!(
-                CoreError::Failed,
-                ["Failed to start OpenARK sender: {error}"]
-            )),
-        }
+        self.start_send_channel(self.channel()).await
+    }
+
+    async fn start_send_channel(&self, channel: &Channel) -> Result<(), ErrorMessage> {
+        assert_started(channel.init_send(self).await, "sender")
     }
 
+    #[inline]
     async fn start_recv(&self) -> Result<(), ErrorMessage> {
-        match self.channel().init_recv(self).await {
-            Ok(Some(_)) => Ok(()),
-            Ok(None) => Err(error_msg!(
-                CoreError::Failed,
-                ["OpenARK client is not inited!"]
-            )),
-            Err(error) => Err(error_msg!(
-                CoreError::Failed,
-                ["Failed to start OpenARK receiver: {error}"]
-            )),
-        }
+        self.start_recv_channel(self.channel()).await
+    }
+
+    async fn start_recv_channel(&self, channel: &Channel) -> Result<(), ErrorMessage> {
+        assert_started(channel.init_recv(self).await, "receiver")
+    }
+
+    #[inline]
+    async fn stop_channel(&self, channel: &Channel) {
+        channel.stop(self).await
     }
 
     #[inline]
-    async fn recv(&self) -> Result<Option<Bytes>, FlowError> {
-        self.channel().recv(self).await
+    async fn recv(&self) -> Result<Option<PipeMessage>, FlowError> {
+        let channel = self.channel();
+        channel.cancellable(channel.recv(self)).await
     }
 
     async fn recv_buffer(
         &self,
-        buffer: Option<&mut BufferRef>,
-    ) -> Result<CreateSuccess, FlowError> {
-        // load a message
-        let message = match self.recv().await? {
-            Some(message) => message,
-            None => return Err(FlowError::Eos),
+        provided: Option<&mut BufferRef>,
+    ) -> Result<CreateSuccess, FlowError>
+    where
+        <Self as ObjectSubclass>::Type: IsA<BaseSrc> + IsA<Element>,
+    {
+        // flush the pending buffers first
+        let pending = ::core::mem::take(&mut *self.channel().pending.lock().unwrap());
+        let (payload_mode, mut buffers) = if pending.is_empty() {
+            let pad = self.obj().src_pad().clone();
+            self.recv_buffers(self.channel(), &pad).await?
+        } else {
+            (self.args().read().await.payload_mode(), pending)
+        };
+        match payload_mode {
+            PayloadMode::List if provided.is_none() => {
+                // the payloads of other caps follow in the next lists
+                let (list, caps) = take_buffer_list(&mut buffers);
+                self.channel().pending.lock().unwrap().extend(buffers);
+                if let Some(caps) = caps {
+                    self.negotiate_src_caps(&caps)?;
+                }
+
+                debug!(
+                    self.cat(),
+                    imp: self,
+                    "Produced buffer list {list:?}",
+                );
+                Ok(CreateSuccess::NewBufferList(list))
+            }
+            // a provided buffer is filled one payload at a time, as no list can be returned then
+            PayloadMode::Buffers | PayloadMode::Last | PayloadMode::List => {
+                let (buffer, caps) = buffers.pop_front().unwrap();
+                self.channel().pending.lock().unwrap().extend(buffers);
+                self.produce_buffer(buffer, caps.as_ref(), provided)
+            }
+        }
+    }
+
+    /// Receives the next message of the given channel as stream buffers,
+    /// forwarding its stream changes to the `pad`.
+    ///
+    /// At least one buffer is returned.
+    async fn recv_buffers(
+        &self,
+        channel: &Channel,
+        pad: &Pad,
+    ) -> Result<(PayloadMode, VecDeque<(Buffer, Option<Caps>)>), FlowError>
+    where
+        <Self as ObjectSubclass>::Type: IsA<Element>,
+    {
+        let (caps, ignore_eos, payload_key, payload_mode, rebase_timestamps) = {
+            let args = self.args().read().await;
+            (
+                args.caps(),
+                args.ignore_eos(),
+                args.payload_key().cloned(),
+                args.payload_mode(),
+                args.rebase_timestamps(),
+            )
         };
+        let model = channel.model().await;
+
+        loop {
+            // load a message
+            let message = match channel.cancellable(channel.recv(self)).await? {
+                Some(message) => message,
+                None => return Err(FlowError::Eos),
+            };
+
+            // handle control messages
+            match ControlMessage::from_message(&message) {
+                Some(ControlMessage::Eos) if ignore_eos => {
+                    debug!(
+                        self.cat(),
+                        imp: self,
+                        "Ignored an end-of-stream message",
+                    );
+                    continue;
+                }
+                Some(ControlMessage::Eos) => {
+                    info!(
+                        self.cat(),
+                        imp: self,
+                        "Received an end-of-stream message",
+                    );
+                    return Err(FlowError::Eos);
+                }
+                None => (),
+            }
+
+            // select payloads, along with their indices in the message
+            let mut payloads: Vec<_> = message
+                .payloads
+                .iter()
+                .filter(|payload| payload.value().is_some())
+                .enumerate()
+                .filter(|(_, payload)| {
+                    payload_key
+                        .as_ref()
+                        .map(|pattern| pattern.matches(payload.key()))
+                        .unwrap_or(true)
+                })
+                .collect();
+            if payload_mode == PayloadMode::Last && payloads.len() > 1 {
+                payloads.drain(..payloads.len() - 1);
+            }
+            if payloads.is_empty() {
+                debug!(
+                    self.cat(),
+                    imp: self,
+                    "Skipped a message without matching payloads",
+                );
+                continue;
+            }
+
+            // forward the stream changes
+            self.forward_stream_info(channel, pad, &message.value).await;
+
+            // restore timestamps and flags of each payload
+            let count = message
+                .payloads
+                .iter()
+                .filter(|payload| payload.value().is_some())
+                .count();
+            let info = BufferInfo::from_value(&message.value);
+            let items = BatchItem::from_value(&message.value).unwrap_or_default();
+            let mut rebase = if rebase_timestamps {
+                Some(channel.rebase.lock().await)
+            } else {
+                None
+            };
+
+            // create stream buffers
+            let mut buffers = VecDeque::with_capacity(payloads.len());
+            for (index, payload) in payloads {
+                let caps = caps
+                    .clone()
+                    .or_else(|| payload::caps_from_message(&message.value, payload.key()));
+
+                let info = match items.get(index).filter(|item| item.key == payload.key()) {
+                    Some(item) => item.buffer.clone(),
+                    None => info.as_ref().map(|info| info.interpolate(index, count)),
+                };
+                let info = match (info, rebase.as_mut()) {
+                    (Some(mut info), Some(rebase)) => {
+                        rebase.apply(&mut info, || self.obj().current_running_time());
+                        Some(info)
+                    }
+                    (info, _) => info,
+                };
+
+                let data = payload.value().cloned().unwrap();
+                channel.sizes.lock().unwrap().push(data.len());
+
+                let mut buffer = crate::buffer::from_bytes(data);
+                {
+                    let buffer = buffer.make_mut();
+                    if let Some(info) = &info {
+                        info.apply(buffer);
+                    }
+
+                    // attach the message
+                    ArkMessageMeta::from_message(&model, payload.key(), &message).add(buffer);
+                }
+                buffers.push_back((buffer, caps));
+            }
+            drop(rebase);
+
+            // mark the first buffer after switching models
+            if channel.discont.swap(false, Ordering::SeqCst) {
+                if let Some((buffer, _)) = buffers.front_mut() {
+                    let buffer = buffer.make_mut();
+                    buffer.set_flags(BufferFlags::DISCONT);
+                }
+            }
+
+            return Ok((payload_mode, buffers));
+        }
+    }
+
+    async fn forward_stream_info(&self, channel: &Channel, pad: &Pad, value: &DynValue) {
+        let info = match StreamInfo::from_value(value) {
+            Some(info) => info,
+            None => return,
+        };
+
+        let mut last = channel.stream.lock().await;
+        if *last == info {
+            return;
+        }
+
+        if let Some(stream_id) = info
+            .stream_id
+            .as_deref()
+            .filter(|&stream_id| last.stream_id.as_deref() != Some(stream_id))
+        {
+            info!(
+                self.cat(),
+                imp: self,
+                "Starting stream {stream_id}",
+            );
+
+            // re-send the sticky events following the stream start
+            let caps = pad.sticky_event::<event::Caps>(0);
+            let segment = pad.sticky_event::<event::Segment>(0);
+            pad.push_event(event::StreamStart::new(stream_id));
+            if let Some(caps) = caps {
+                pad.push_event(Event::from(caps));
+            }
+            if let Some(segment) = segment {
+                pad.push_event(Event::from(segment));
+            }
+        }
+
+        if info.tags != last.tags || info.global_tags != last.global_tags {
+            for tags in info.tag_lists() {
+                debug!(
+                    self.cat(),
+                    imp: self,
+                    "Forwarding tags {tags:?}",
+                );
+                pad.push_event(event::Tag::new(tags));
+            }
+        }
 
-        // TODO: is buffer used?
-        if buffer.is_some() {
-            todo!();
+        *last = info;
+    }
+
+    fn produce_buffer(
+        &self,
+        buffer: Buffer,
+        caps: Option<&Caps>,
+        provided: Option<&mut BufferRef>,
+    ) -> Result<CreateSuccess, FlowError>
+    where
+        <Self as ObjectSubclass>::Type: IsA<BaseSrc>,
+    {
+        // negotiate caps
+        if let Some(caps) = caps {
+            self.negotiate_src_caps(caps)?;
+        }
+
+        // fill the provided buffer if the payload fits
+        if let Some(provided) = provided.filter(|provided| provided.maxsize() >= buffer.size()) {
+            self.fill_buffer(&buffer, provided)?;
+
+            debug!(
+                self.cat(),
+                imp: self,
+                "Filled buffer {provided:?}",
+            );
+            return Ok(CreateSuccess::FilledBuffer);
         }
 
-        // create a stream buffer
-        let buffer = Buffer::from_slice(message);
+        // or a buffer of the pool proposed by downstream
+        if let Some(mut pooled) = self.acquire_pool_buffer(buffer.size()) {
+            self.fill_buffer(&buffer, pooled.make_mut())?;
+
+            debug!(
+                self.cat(),
+                imp: self,
+                "Produced pooled buffer {pooled:?}",
+            );
+            return Ok(CreateSuccess::NewBuffer(pooled));
+        }
 
         debug!(
             self.cat(),
@@ -144,38 +586,216 @@ where
         Ok(CreateSuccess::NewBuffer(buffer))
     }
 
+    /// Acquire a buffer fitting the payload from the pool proposed by downstream, if any.
+    ///
+    /// The default pool of the element is skipped, as wrapping the payload avoids a copy.
+    fn acquire_pool_buffer(&self, size: usize) -> Option<Buffer>
+    where
+        <Self as ObjectSubclass>::Type: IsA<BaseSrc>,
+    {
+        let pool = self
+            .obj()
+            .buffer_pool()
+            .filter(|pool| pool.type_() != BufferPool::static_type() && pool.is_active())?;
+        pool.acquire_buffer(None)
+            .ok()
+            .filter(|buffer| buffer.maxsize() >= size)
+    }
+
+    /// Copy the payload, along with its metadata, into the given buffer.
+    fn fill_buffer(&self, buffer: &Buffer, target: &mut BufferRef) -> Result<(), FlowError> {
+        target.set_size(buffer.size());
+        buffer
+            .copy_into(
+                target,
+                BufferCopyFlags::FLAGS | BufferCopyFlags::TIMESTAMPS | BufferCopyFlags::META,
+                ..,
+            )
+            .map_err(|error| {
+                error!(
+                    self.cat(),
+                    imp: self,
+                    "Failed to copy buffer metadata: {error}",
+                );
+                FlowError::Error
+            })?;
+
+        let mut map = target.map_writable().map_err(|_| {
+            error!(
+                self.cat(),
+                imp: self,
+                "Failed to map the target buffer",
+            );
+            FlowError::Error
+        })?;
+        buffer
+            .copy_to_slice(0, map.as_mut_slice())
+            .map_err(|_| FlowError::Error)
+    }
+
+    fn negotiate_src_caps(&self, caps: &Caps) -> Result<(), FlowError>
+    where
+        <Self as ObjectSubclass>::Type: IsA<BaseSrc>,
+    {
+        let element = self.obj();
+        let current_caps = element.static_pad("src").and_then(|pad| pad.current_caps());
+        if current_caps.as_ref() == Some(caps) {
+            return Ok(());
+        }
+
+        info!(
+            self.cat(),
+            imp: self,
+            "Negotiating caps {caps}",
+        );
+        element.set_caps(caps).map_err(|error| {
+            error!(
+                self.cat(),
+                imp: self,
+                "Failed to negotiate caps {caps}: {error}",
+            );
+            FlowError::NotNegotiated
+        })
+    }
+
+    #[inline]
+    async fn send(&self, data: PipeMessage) -> Result<(), FlowError> {
+        let channel = self.channel();
+        channel.cancellable(channel.send(self, data)).await
+    }
+
+    /// Publish an end-of-stream message, waiting for all messages to be published.
     #[inline]
-    async fn send(&self, data: PipeMessage<Image>) -> Result<(), FlowError> {
-        self.channel().send(self, data).await
+    async fn send_eos(&self) -> Result<(), FlowError> {
+        self.send_channel_eos(self.channel()).await
     }
 
-    async fn send_buffer(&self, key: String, buffer: &Buffer) -> Result<FlowSuccess, FlowError> {
-        // TODO: handle other media types (audio, JSON, plain, ...)
-        // TODO: support non-image(video) data using sink Caps and cache it
+    async fn send_channel_eos(&self, channel: &Channel) -> Result<(), FlowError> {
+        self.flush_channel_batch(channel).await?;
+        self.send_channel_message(channel, ControlMessage::Eos.to_message())
+            .await?;
+
+        channel.cancellable(channel.flush()).await
+    }
+
+    #[inline]
+    async fn send_buffer(
+        &self,
+        key: String,
+        caps: Option<&CapsRef>,
+        stream: Option<&StreamInfo>,
+        buffer: &Buffer,
+    ) -> Result<FlowSuccess, FlowError> {
+        self.send_channel_buffer(self.channel(), key, caps, stream, buffer)
+            .await
+    }
+
+    /// Publish the buffer on the given channel of this element, e.g. of a request pad.
+    async fn send_channel_buffer(
+        &self,
+        channel: &Channel,
+        key: String,
+        caps: Option<&CapsRef>,
+        stream: Option<&StreamInfo>,
+        buffer: &Buffer,
+    ) -> Result<FlowSuccess, FlowError> {
+        // parse the payload kind
+        let kind = caps.map(PayloadKind::from_caps).unwrap_or_default();
+
+        // build a message value
+        let mut value = self
+            .args()
+            .read()
+            .await
+            .value()
+            .cloned()
+            .unwrap_or_else(|| DynValue::Object(Default::default()));
+        if let Some(meta) = ArkMessageMeta::from_buffer(buffer) {
+            // relay the upstream value, but not its stale batch, stream and control info
+            payload::merge_value(&mut value, meta.into_relayed_value());
+        }
+        payload::merge_value(&mut value, kind.build_value(&key, caps));
+        let mut info = BufferInfo::from_buffer(buffer);
+        if channel.discont.swap(false, Ordering::SeqCst) {
+            info.set_discont();
+        }
+        info.insert_into(&mut value);
+        if let Some(stream) = stream {
+            stream.insert_into(&mut value);
+        }
+
         // build a payload
-        let key_ref = format!("@data:image,{key}");
-        let payload = PipePayload::new(
-            key,
-            Some(Bytes::from(buffer.map_readable().unwrap().to_vec())),
-        );
+        let data = crate::buffer::to_bytes(buffer).ok_or_else(|| {
+            error!(
+                self.cat(),
+                imp: self,
+                "Failed to map buffer {buffer:?}",
+            );
+            FlowError::Error
+        })?;
+        let payload = PipePayload::new(key, Some(data));
 
         // build a message
-        // TODO: handle other media types (audio, JSON, plain, ...)
-        // TODO: to be implemented
-        let value = Image::default();
-        let message = PipeMessage::with_payloads(vec![payload], value);
+        let (batch_size, batch_duration) = {
+            let args = self.args().read().await;
+            (args.batch_size(), args.batch_duration())
+        };
+        let message = {
+            let mut batch = channel.batch.lock().await;
+            let first = batch.is_empty();
+            batch.push(payload, value);
+            if !batch.is_full(batch_size, batch_duration) {
+                // flush the partial batch on time, even if the stream stalls
+                if first && batch_duration > 0 {
+                    let delay = Duration::from_nanos(batch_duration);
+                    channel.start_batch_timer(self, delay).await;
+                }
+                return Ok(FlowSuccess::Ok);
+            }
+            batch.take()
+        };
 
         // encode and send
-        self.send(message)
+        match message {
+            Some(message) => self.send_channel_message(channel, message).await,
+            None => Ok(FlowSuccess::Ok),
+        }
+    }
+
+    /// Publish the accumulated buffers, if any.
+    #[inline]
+    async fn flush_batch(&self) -> Result<FlowSuccess, FlowError> {
+        self.flush_channel_batch(self.channel()).await
+    }
+
+    async fn flush_channel_batch(&self, channel: &Channel) -> Result<FlowSuccess, FlowError> {
+        let message = channel.batch.lock().await.take();
+        match message {
+            Some(message) => self.send_channel_message(channel, message).await,
+            None => Ok(FlowSuccess::Ok),
+        }
+    }
+
+    async fn send_channel_message(
+        &self,
+        channel: &Channel,
+        message: PipeMessage,
+    ) -> Result<FlowSuccess, FlowError> {
+        channel
+            .cancellable(channel.send(self, message))
             .await
             .map(|()| FlowSuccess::Ok)
-            .map_err(|error| {
-                error!(
-                    self.cat(),
-                    imp: self,
-                    "{error}",
-                );
-                FlowError::Error
+            .map_err(|error| match error {
+                // unlocked by a state change or a flush
+                FlowError::Flushing => error,
+                error => {
+                    error!(
+                        self.cat(),
+                        imp: self,
+                        "{error}",
+                    );
+                    FlowError::Error
+                }
             })
     }
 
@@ -197,18 +817,212 @@ impl<T> ChannelSubclassExt for T where Self: ChannelSubclass + PluginImpl {}
 
 #[derive(Default)]
 pub struct Channel {
+    batch: Arc<Mutex<Batch>>,
+    batch_timer: ::std::sync::Mutex<Option<JoinHandle<()>>>,
     builder: RwLock<Option<ChannelBuilder>>,
-    client: RwLock<Option<PipeClient<Image>>>,
+    cancel: ::std::sync::Mutex<CancellationToken>,
+    client: RwLock<Option<Arc<PipeClient>>>,
+    discont: AtomicBool,
+    pending: ::std::sync::Mutex<VecDeque<(Buffer, Option<Caps>)>>,
+    rebase: Mutex<TimestampRebase>,
+    sizes: ::std::sync::Mutex<RecentSizes>,
     recv: Mutex<Option<self::recv::Queue>>,
+    recv_backoff: Mutex<Backoff>,
     send: RwLock<Option<self::send::Queue>>,
+    send_backlog: Arc<Mutex<VecDeque<PipeMessage>>>,
+    send_backoff: Mutex<Backoff>,
+    stats: Arc<Stats>,
+    stream: Mutex<StreamInfo>,
+    started: AtomicBool,
+    stats_task: Mutex<Option<JoinHandle<()>>>,
+    switch: Notify,
 }
 
 impl Channel {
+    #[inline]
+    pub fn stats(&self) -> &Stats {
+        &self.stats
+    }
+
+    /// Whether the channel is started, i.e. bound to a model.
+    #[inline]
+    pub fn is_started(&self) -> bool {
+        self.started.load(Ordering::SeqCst)
+    }
+
+    /// Buffer size fitting the recently received payloads, or 0 if unknown.
+    pub fn size_hint(&self) -> usize {
+        self.sizes.lock().unwrap().max()
+    }
+
+    /// Cancels the pending and upcoming recv/send calls, until [`Self::unlock_stop`].
+    pub fn unlock(&self) {
+        self.cancel.lock().unwrap().cancel();
+    }
+
+    /// Lets the recv/send calls run again, dropping the buffers received before flushing.
+    pub fn unlock_stop(&self) {
+        self.pending.lock().unwrap().clear();
+
+        let mut cancel = self.cancel.lock().unwrap();
+        if cancel.is_cancelled() {
+            *cancel = CancellationToken::new();
+        }
+    }
+
+    /// Runs the given future, unless the channel is unlocked.
+    pub async fn cancellable<T>(
+        &self,
+        f: impl Future<Output = Result<T, FlowError>>,
+    ) -> Result<T, FlowError> {
+        let cancel = self.cancel.lock().unwrap().clone();
+
+        select! {
+            biased;
+            () = cancel.cancelled() => Err(FlowError::Flushing),
+            result = f => result,
+        }
+    }
+
+    /// Points the channel to another model, returning the previous one if changed.
+    ///
+    /// The stale subscriber or publisher is replaced on the next recv/send call.
+    async fn switch_model(&self, model: String) -> Option<String> {
+        let old_model = {
+            let mut lock = self.builder.write().await;
+            let builder = lock.as_mut()?;
+            if builder.model == model {
+                return None;
+            }
+            ::core::mem::replace(&mut builder.model, model)
+        };
+
+        // wake up the pending recv calls
+        self.switch.notify_waiters();
+
+        self.discont.store(true, Ordering::SeqCst);
+        self.pending.lock().unwrap().clear();
+        self.rebase.lock().await.reset();
+        *self.stream.lock().await = StreamInfo::default();
+        Some(old_model)
+    }
+
+    /// Model name of the started channel, or empty if not started.
+    async fn model(&self) -> String {
+        self.builder
+            .read()
+            .await
+            .as_ref()
+            .map(|builder| builder.model.clone())
+            .unwrap_or_default()
+    }
+
+    /// Whether the queue of the given model is left behind by [`Self::switch_model`].
+    async fn is_stale(&self, model: &str) -> bool {
+        self.builder
+            .read()
+            .await
+            .as_ref()
+            .map(|builder| builder.model != model)
+            .unwrap_or_default()
+    }
+
+    /// Publishes the partial batch once the `delay` passes, replacing the former deadline.
+    async fn start_batch_timer(
+        &self,
+        imp: &(impl ?Sized + ChannelSubclassExt + PluginImpl),
+        delay: Duration,
+    ) {
+        let queue = match self.send.read().await.as_ref() {
+            Some(queue) => queue.sender(),
+            None => return,
+        };
+
+        let backlog = self.send_backlog.clone();
+        let batch = self.batch.clone();
+        let cat = imp.cat();
+        let task = imp.runtime().spawn(async move {
+            ::tokio::time::sleep(delay).await;
+
+            // keep the batch locked, so that the next messages follow this one
+            let mut batch = batch.lock().await;
+
+            // leave the batch to the next buffer, which reconnects or switches the publisher
+            if queue.is_closed() {
+                return;
+            }
+            let message = match batch.take() {
+                Some(message) => message,
+                None => return,
+            };
+            match queue.push(message).await {
+                Ok(0) => (),
+                Ok(dropped) => debug!(cat, "Dropped {dropped} message(s)"),
+                // closed in the meantime, so retry it after reconnecting
+                Err(message) => {
+                    warning!(
+                        cat,
+                        "Failed to flush the batch, deferring it to the next message"
+                    );
+                    backlog.lock().await.push_back(message);
+                }
+            }
+        });
+
+        let mut lock = self.batch_timer.lock().unwrap();
+        if let Some(task) = lock.replace(task) {
+            task.abort();
+        }
+    }
+
+    async fn start_stats(&self, runtime: &Runtime, element: WeakRef<Element>, interval: u32) {
+        self.stats.reset();
+
+        let task = (interval > 0).then(|| {
+            let stats = self.stats.clone();
+            runtime.spawn(async move {
+                let mut interval = ::tokio::time::interval(Duration::from_millis(interval.into()));
+                interval.tick().await;
+                loop {
+                    interval.tick().await;
+
+                    // stop posting once the element is gone
+                    let element = match element.upgrade() {
+                        Some(element) => element,
+                        None => break,
+                    };
+                    let message = message::Element::builder(stats.to_structure())
+                        .src(&element)
+                        .build();
+                    // the element may not be in a bin
+                    let _ = element.post_message(message);
+                }
+            })
+        });
+
+        let mut lock = self.stats_task.lock().await;
+        if let Some(task) = ::core::mem::replace(&mut *lock, task) {
+            task.abort();
+        }
+    }
+
     async fn init_recv(
         &self,
         imp: &(impl ?Sized + ChannelSubclassExt + PluginImpl),
     ) -> Result<Option<MappedMutexGuard<'_, self::recv::Queue>>> {
         let mut lock = self.recv.lock().await;
+
+        // replace the queue left behind by switching models
+        let stale = match lock.as_ref() {
+            Some(queue) => self.is_stale(queue.model()).await,
+            None => false,
+        };
+        if stale {
+            if let Some(queue) = lock.take() {
+                queue.stop(imp).await
+            }
+        }
+
         match lock.as_mut() {
             Some(_) => Ok(Some(sync::mutex::unwrap_lock(lock))),
             None => {
@@ -218,7 +1032,7 @@ impl Channel {
                         let client_lock = self.client.read().await;
                         let client = assert_client(&client_lock)?;
 
-                        lock.replace(builder.build_receiver(client, imp).await?);
+                        lock.replace(builder.build_receiver(client, imp, &self.stats).await?);
                         drop(client_lock);
                         drop(builder_lock);
 
@@ -234,6 +1048,22 @@ impl Channel {
         &self,
         imp: &(impl ?Sized + ChannelSubclassExt + PluginImpl),
     ) -> Result<Option<RwLockReadGuard<'_, self::send::Queue>>> {
+        // replace the queue left behind by switching models
+        let stale = match self.send.read().await.as_ref() {
+            Some(queue) => self.is_stale(queue.model()).await,
+            None => false,
+        };
+        if stale {
+            let maybe_queue = self.send.write().await.take();
+            if let Some(queue) = maybe_queue {
+                // publish the messages queued before switching
+                if !queue.is_failed() {
+                    queue.flush().await;
+                }
+                queue.stop(imp).await
+            }
+        }
+
         let lock = self.send.read().await;
         match lock.as_ref() {
             Some(_) => Ok(Some(sync::rwlock::unwrap_lock(lock))),
@@ -247,7 +1077,7 @@ impl Channel {
                         let client = assert_client(&client_lock)?;
 
                         let mut lock = self.send.write().await;
-                        lock.replace(builder.build_sender(client, imp).await?);
+                        lock.replace(builder.build_sender(client, imp, &self.stats).await?);
                         drop(client_lock);
                         drop(builder_lock);
                         drop(lock);
@@ -264,32 +1094,141 @@ impl Channel {
     async fn recv(
         &self,
         imp: &(impl ?Sized + ChannelSubclassExt + PluginImpl),
-    ) -> Result<Option<Bytes>, FlowError> {
-        let maybe_queue = self.init_recv(imp).await.map_err(|error| {
-            error!(imp.cat(), imp: imp, "{error}");
-            FlowError::Error
-        })?;
+    ) -> Result<Option<PipeMessage>, FlowError> {
+        loop {
+            // subscribe before checking the model, not to miss any switch
+            let switched = self.switch.notified();
 
-        match maybe_queue {
-            Some(mut queue) => Ok(queue.recv().await),
-            None => Ok(None),
+            let maybe_queue = match self.init_recv(imp).await {
+                Ok(maybe_queue) => maybe_queue,
+                Err(error) => {
+                    error!(imp.cat(), imp: imp, "{error}");
+                    self.reconnect(imp, &self.recv_backoff, "subscriber")
+                        .await?;
+                    continue;
+                }
+            };
+
+            let mut queue = match maybe_queue {
+                Some(queue) => queue,
+                None => return Ok(None),
+            };
+            let message = select! {
+                message = queue.recv() => message,
+                // replace the stale queue
+                () = switched => continue,
+            };
+            match message {
+                Some(message) => {
+                    drop(queue);
+                    if self.recv_backoff.lock().await.reset() {
+                        post_warning(imp, "Reconnected OpenARK subscriber");
+                    }
+                    return Ok(Some(message));
+                }
+                None if queue.is_failed() => {
+                    drop(queue);
+
+                    // drop the broken queue
+                    let maybe_queue = self.recv.lock().await.take();
+                    if let Some(queue) = maybe_queue {
+                        queue.stop(imp).await
+                    }
+                    self.reconnect(imp, &self.recv_backoff, "subscriber")
+                        .await?;
+                }
+                None => return Ok(None),
+            }
         }
     }
 
     async fn send(
         &self,
         imp: &(impl ?Sized + ChannelSubclassExt + PluginImpl),
-        data: PipeMessage<Image>,
+        data: PipeMessage,
     ) -> Result<(), FlowError> {
-        let maybe_queue = self.init_send(imp).await.map_err(|error| {
-            error!(imp.cat(), imp: imp, "{error}");
-            FlowError::Error
-        })?;
+        loop {
+            let maybe_queue = match self.init_send(imp).await {
+                Ok(maybe_queue) => maybe_queue,
+                Err(error) => {
+                    error!(imp.cat(), imp: imp, "{error}");
+                    self.reconnect(imp, &self.send_backoff, "publisher").await?;
+                    continue;
+                }
+            };
+
+            let queue = match maybe_queue {
+                Some(queue) => queue,
+                None => return Err(FlowError::Eos),
+            };
+            if !queue.is_failed() {
+                if self.send_backoff.lock().await.reset() {
+                    post_warning(imp, "Reconnected OpenARK publisher");
+                }
+
+                // retry the messages left by the broken publisher first
+                let backlog = ::core::mem::take(&mut *self.send_backlog.lock().await);
+                for message in backlog {
+                    queue.send(imp, message).await?;
+                }
+                return queue.send(imp, data).await;
+            }
+            drop(queue);
+
+            // drop the broken queue, but not its messages
+            let maybe_queue = self.send.write().await.take();
+            if let Some(queue) = maybe_queue {
+                self.send_backlog.lock().await.extend(queue.take_backlog());
+                queue.stop(imp).await
+            }
+            self.reconnect(imp, &self.send_backoff, "publisher").await?;
+        }
+    }
 
-        match maybe_queue {
-            Some(queue) => queue.send(imp, data).await,
-            None => Err(FlowError::Eos),
+    async fn flush(&self) -> Result<(), FlowError> {
+        if let Some(queue) = self.send.read().await.as_ref() {
+            queue.flush().await;
         }
+        Ok(())
+    }
+
+    /// Waits for the next reconnect attempt, unless the retries are exhausted.
+    async fn reconnect(
+        &self,
+        imp: &(impl ?Sized + ChannelSubclassExt + PluginImpl),
+        backoff: &Mutex<Backoff>,
+        label: &str,
+    ) -> Result<(), FlowError> {
+        let args = self
+            .builder
+            .read()
+            .await
+            .as_ref()
+            .map(|builder| builder.reconnect.clone())
+            .unwrap_or_default();
+
+        let mut backoff = backoff.lock().await;
+        let delay = match backoff.next_delay(&args) {
+            Some(delay) => delay,
+            None => {
+                let attempts = backoff.attempts();
+                error!(
+                    imp.cat(),
+                    imp: imp,
+                    "Gave up reconnecting OpenARK {label} after {attempts} attempt(s)",
+                );
+                return Err(FlowError::Error);
+            }
+        };
+        let attempt = backoff.attempts();
+        drop(backoff);
+
+        post_warning(
+            imp,
+            &format!("Lost OpenARK {label}, reconnecting in {delay:?} (attempt {attempt})"),
+        );
+        ::tokio::time::sleep(delay).await;
+        Ok(())
     }
 
     async fn stop(&self, imp: &(impl ?Sized + ChannelSubclassExt + PluginImpl)) {
@@ -313,19 +1252,54 @@ impl Channel {
             }
         };
 
+        self.started.store(false, Ordering::SeqCst);
         join!(stop_recv, stop_send);
+        if let Some(task) = self.stats_task.lock().await.take() {
+            task.abort();
+        }
+        if let Some(task) = self.batch_timer.lock().unwrap().take() {
+            task.abort();
+        }
+        self.batch.lock().await.take();
+        self.discont.store(false, Ordering::SeqCst);
+        self.pending.lock().unwrap().clear();
+        self.rebase.lock().await.reset();
+        *self.stream.lock().await = StreamInfo::default();
+        self.recv_backoff.lock().await.reset();
+        self.send_backlog.lock().await.clear();
+        self.send_backoff.lock().await.reset();
+
+        // release the shared client
+        self.client.write().await.take();
     }
 }
 
 struct ChannelBuilder {
+    client: ClientArgs,
     model: String,
     otlp: bool,
+    queue: QueueLimits,
+    reconnect: ReconnectArgs,
 }
 
 impl ChannelBuilder {
     #[inline]
     fn new(model: String) -> Self {
-        Self { model, otlp: false }
+        Self {
+            client: ClientArgs::default(),
+            model,
+            otlp: false,
+            queue: QueueLimits::default(),
+            reconnect: ReconnectArgs::default(),
+        }
+    }
+
+    #[inline]
+    fn client(self, value: ClientArgs) -> Self {
+        Self {
+            client: value,
+            ..self
+        }
     }
 
     #[inline]
@@ -336,36 +1310,65 @@ impl ChannelBuilder {
         }
     }
 
+    #[inline]
+    fn queue(self, value: QueueLimits) -> Self {
+        Self {
+            queue: value,
+            ..self
+        }
+    }
+
+    #[inline]
+    fn reconnect(self, value: ReconnectArgs) -> Self {
+        Self {
+            reconnect: value,
+            ..self
+        }
+    }
+
     async fn build(self, channel: &Channel) -> Result<(), ErrorMessage> {
         tracer::init_once_with_default(self.otlp);
 
-        {
+        let client = self.client.clone();
+        let client_changed = {
             let mut lock = channel.builder.write().await;
-            lock.replace(self);
-        }
+            lock.replace(self)
+                .map(|builder| builder.client != client)
+                .unwrap_or_default()
+        };
 
         {
             let mut lock = channel.client.write().await;
-            if lock.is_none() {
-                lock.replace(try_init_client().await?);
+            if lock.is_none() || client_changed {
+                lock.replace(ClientPool::global().get(&client).await?);
             }
         }
 
+        channel.started.store(true, Ordering::SeqCst);
         Ok(())
     }
 
     async fn build_receiver<'c>(
         &self,
-        client: &'c PipeClient<Image>,
+        client: &'c PipeClient,
         imp: &(impl ?Sized + ChannelSubclassExt + PluginImpl),
+        stats: &Arc<Stats>,
     ) -> Result<self::recv::Queue, FlowError> {
-        let Self { model, otlp: _ } = self;
+        let Self {
+            client: _,
+            model,
+            otlp: _,
+            queue,
+            reconnect: _,
+        } = self;
 
         let args = QueueArgs {
             client,
             imp,
             label: "subscriber",
+            limits: queue.clone(),
             model: model.clone(),
+            stats: stats.clone(),
         };
 
         self::recv::Queue::try_new(args).await
@@ -373,16 +1376,25 @@ impl ChannelBuilder {
 
     async fn build_sender<'c>(
         &self,
-        client: &'c PipeClient<Image>,
+        client: &'c PipeClient,
         imp: &(impl ?Sized + ChannelSubclassExt + PluginImpl),
+        stats: &Arc<Stats>,
     ) -> Result<self::send::Queue, FlowError> {
-        let Self { model, otlp: _ } = self;
+        let Self {
+            client: _,
+            model,
+            otlp: _,
+            queue,
+            reconnect: _,
+        } = self;
 
         let args = QueueArgs {
             client,
             imp,
             label: "publisher",
+            limits: queue.clone(),
             model: model.clone(),
+            stats: stats.clone(),
         };
 
         self::send::Queue::try_new(args).await
@@ -393,10 +1405,12 @@ struct QueueArgs<'c, C>
 where
     C: ?Sized,
 {
-    client: &'c PipeClient<Image>,
+    client: &'c PipeClient,
     imp: &'c C,
     label: &'static str,
+    limits: QueueLimits,
     model: String,
+    stats: Arc<Stats>,
 }
 
 impl<'c, C> QueueArgs<'c, C>
@@ -405,7 +1419,7 @@ where
 {
     async fn call_client<F, Fut, R>(&self, f: F) -> Result<R, FlowError>
     where
-        F: FnOnce(&'c PipeClient<Image>, Name) -> Fut,
+        F: FnOnce(&'c PipeClient, Name) -> Fut,
         Fut: Future<Output = Result<R>>,
     {
         let model = self.model.parse().map_err(|error| {
@@ -430,13 +1444,14 @@ where
     }
 }
 
-pub async fn try_init_client<T>() -> Result<PipeClient<T>, ErrorMessage>
+pub async fn try_init_client<T>(client: &ClientArgs) -> Result<PipeClient<T>, ErrorMessage>
 where
     T: JsonSchema,
 {
     // Do not parse arguments from command line,
-    // only use the environment variables.
-    let args = PipeClientArgs::try_parse_from::<_, &str>([]).map_err(|error| {
+    // only use the properties and then the environment variables.
+    let args = ["gsark".into()].into_iter().chain(client.to_cli_args());
+    let args = PipeClientArgs::try_parse_from(args).map_err(|error| {
         error_msg!(
             CoreError::Failed,
             ["Failed to parse OpenARK arguments: {error}"]
@@ -451,10 +1466,63 @@ where
     })
 }
 
+fn post_warning(imp: &(impl ?Sized + PluginImpl), text: &str) {
+    warning!(imp.cat(), imp: imp, "{text}");
+
+    let obj = imp.obj();
+    if let Some(element) = obj.dynamic_cast_ref::<Element>() {
+        let message = message::Warning::builder(CoreError::Failed, text)
+            .src(element)
+            .build();
+        // the element may not be in a bin
+        let _ = element.post_message(message);
+    }
+}
+
+fn assert_started<T>(result: Result<Option<T>>, label: &str) -> Result<(), ErrorMessage> {
+    match result {
+        Ok(Some(_)) => Ok(()),
+        Ok(None) => Err(error_msg!(
+            CoreError::Failed,
+            ["OpenARK client is not inited!"]
+        )),
+        Err(error) => Err(error_msg!(
+            CoreError::Failed,
+            ["Failed to start OpenARK {label}: {error}"]
+        )),
+    }
+}
+
+/// Takes the leading stream buffers sharing the same caps as a list, along with the caps.
+///
+/// The buffers without caps follow the caps of the previous ones.
+pub fn take_buffer_list(
+    buffers: &mut VecDeque<(Buffer, Option<Caps>)>,
+) -> (BufferList, Option<Caps>) {
+    let mut list = BufferList::new_sized(buffers.len());
+    let mut list_caps = None;
+    {
+        let list = list.get_mut().unwrap();
+        while let Some((buffer, caps)) = buffers.pop_front() {
+            match (&list_caps, caps) {
+                (_, None) => (),
+                (None, Some(caps)) if list.is_empty() => list_caps = Some(caps),
+                (Some(last), Some(caps)) if *last == caps => (),
+                (_, caps) => {
+                    buffers.push_front((buffer, caps));
+                    break;
+                }
+            }
+            list.add(buffer);
+        }
+    }
+    (list, list_caps)
+}
+
 fn assert_client<'c>(
-    client: &'c RwLockReadGuard<'c, Option<PipeClient<Image>>>,
-) -> Result<&'c PipeClient<Image>> {
+    client: &'c RwLockReadGuard<'c, Option<Arc<PipeClient>>>,
+) -> Result<&'c PipeClient> {
     client
-        .as_ref()
+        .as_deref()
         .ok_or_else(|| anyhow!("OpenARK client is not inited!"))
 }
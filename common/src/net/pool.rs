@@ -0,0 +1,58 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Weak},
+};
+
+use dash_pipe_provider::PipeClient;
+use gst::{error_msg, CoreError, ErrorMessage};
+use once_cell::sync::Lazy;
+use tokio::sync::Mutex;
+
+use crate::{args::ClientArgs, runtime};
+
+/// Process-wide pool of the OpenARK clients, keyed by their configuration
+///
+/// The elements with the same configuration share a client,
+/// which is torn down when the last element drops it.
+pub struct ClientPool {
+    clients: Mutex<HashMap<ClientArgs, Weak<PipeClient>>>,
+}
+
+impl ClientPool {
+    pub fn global() -> &'static Self {
+        static POOL: Lazy<ClientPool> = Lazy::new(|| ClientPool {
+            clients: Mutex::default(),
+        });
+
+        &POOL
+    }
+
+    pub async fn get(&self, args: &ClientArgs) -> Result<Arc<PipeClient>, ErrorMessage> {
+        let mut clients = self.clients.lock().await;
+
+        // forget the torn down clients
+        clients.retain(|_, client| client.strong_count() > 0);
+
+        if let Some(client) = clients.get(args).and_then(Weak::upgrade) {
+            return Ok(client);
+        }
+
+        let client = {
+            let args = args.clone();
+            // the connections should outlive the runtime of any element
+            runtime::shared()
+                .spawn(async move { super::try_init_client(&args).await })
+                .await
+                .map_err(|error| {
+                    error_msg!(
+                        CoreError::Failed,
+                        ["Failed to init OpenARK client: {error}"]
+                    )
+                })??
+        };
+
+        let client = Arc::new(client);
+        clients.insert(args.clone(), Arc::downgrade(&client));
+        Ok(client)
+    }
+}
@@ -0,0 +1,275 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+use dash_pipe_provider::PipeMessage;
+use tokio::sync::Notify;
+
+use crate::{
+    args::{QueueLeaky, QueueLimits},
+    buffer::BufferInfo,
+};
+
+use super::stats::Stats;
+
+pub(super) trait QueueItem {
+    /// Payload size in bytes
+    fn size(&self) -> usize;
+
+    /// Timestamp in nanoseconds, if any
+    fn timestamp(&self) -> Option<u64>;
+}
+
+impl QueueItem for PipeMessage {
+    fn size(&self) -> usize {
+        self.payloads
+            .iter()
+            .filter_map(|payload| payload.value())
+            .map(|value| value.len())
+            .sum()
+    }
+
+    fn timestamp(&self) -> Option<u64> {
+        BufferInfo::from_value(&self.value).and_then(|info| info.dts.or(info.pts))
+    }
+}
+
+/// Bounded async queue, following the `queue` element semantics
+pub(super) struct BoundedQueue<T> {
+    done: Notify,
+    limits: QueueLimits,
+    readable: Notify,
+    state: Mutex<State<T>>,
+    stats: Arc<Stats>,
+    writable: Notify,
+}
+
+impl<T> BoundedQueue<T>
+where
+    T: QueueItem,
+{
+    pub(super) fn new(limits: QueueLimits, stats: Arc<Stats>) -> Self {
+        Self {
+            done: Notify::default(),
+            limits,
+            readable: Notify::default(),
+            state: Mutex::new(State {
+                bytes: 0,
+                closed: false,
+                failed: false,
+                items: VecDeque::default(),
+                unfinished: 0,
+            }),
+            stats,
+            writable: Notify::default(),
+        }
+    }
+
+    /// Push an item, returning the number of dropped items.
+    ///
+    /// The item is given back if the queue is closed.
+    pub(super) async fn push(&self, item: T) -> Result<usize, T> {
+        let entry = Entry::new(item);
+        loop {
+            let writable = self.writable.notified();
+            {
+                let mut state = self.state.lock().unwrap();
+                if state.closed {
+                    return Err(entry.item);
+                }
+
+                if !state.is_full(&self.limits, &entry) {
+                    self.stats.on_push(entry.size);
+                    state.push(entry);
+                    state.update_stats(&self.stats);
+                    self.readable.notify_one();
+                    return Ok(0);
+                }
+
+                match self.limits.leaky {
+                    // wait for the queue to be writable
+                    QueueLeaky::No => (),
+                    // drop the new item
+                    QueueLeaky::Upstream => {
+                        self.stats.on_push(entry.size);
+                        self.stats.on_drop(1);
+                        return Ok(1);
+                    }
+                    // drop the old items
+                    QueueLeaky::Downstream => {
+                        let mut dropped = 0;
+                        while state.is_full(&self.limits, &entry) && state.pop().is_some() {
+                            dropped += 1;
+                        }
+
+                        self.stats.on_push(entry.size);
+                        self.stats.on_drop(dropped);
+                        state.unfinished -= dropped;
+                        state.push(entry);
+                        state.update_stats(&self.stats);
+                        self.readable.notify_one();
+                        return Ok(dropped);
+                    }
+                }
+            }
+            writable.await;
+        }
+    }
+
+    /// Pop an item, waiting for it if the queue is empty.
+    ///
+    /// Returns `None` if the queue is closed and empty.
+    pub(super) async fn pop(&self) -> Option<T> {
+        loop {
+            let readable = self.readable.notified();
+            {
+                let mut state = self.state.lock().unwrap();
+                if let Some(entry) = state.pop() {
+                    self.stats.on_pop(entry.size, entry.queued.elapsed());
+                    state.update_stats(&self.stats);
+                    self.writable.notify_waiters();
+                    return Some(entry.item);
+                }
+                if state.closed {
+                    return None;
+                }
+            }
+            readable.await;
+        }
+    }
+
+    pub(super) fn close(&self) {
+        self.state.lock().unwrap().closed = true;
+        self.readable.notify_waiters();
+        self.writable.notify_waiters();
+        self.done.notify_waiters();
+    }
+
+    /// Mark a popped item as processed.
+    pub(super) fn task_done(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.unfinished = state.unfinished.saturating_sub(1);
+        if state.unfinished == 0 {
+            self.done.notify_waiters();
+        }
+    }
+
+    /// Wait until all the pushed items are processed, or the queue is closed.
+    pub(super) async fn join(&self) {
+        loop {
+            let done = self.done.notified();
+            {
+                let state = self.state.lock().unwrap();
+                if state.unfinished == 0 || state.closed {
+                    return;
+                }
+            }
+            done.await;
+        }
+    }
+
+    /// Put a popped but unprocessed item back in front, e.g. to retry it.
+    pub(super) fn requeue(&self, item: T) {
+        let entry = Entry::new(item);
+        let mut state = self.state.lock().unwrap();
+        state.bytes += entry.size;
+        state.items.push_front(entry);
+        state.update_stats(&self.stats);
+    }
+
+    /// Take all the queued items, e.g. to move them into another queue.
+    pub(super) fn drain(&self) -> Vec<T> {
+        let mut state = self.state.lock().unwrap();
+        let items = state.items.drain(..).map(|entry| entry.item).collect();
+        state.bytes = 0;
+        state.unfinished = 0;
+        state.update_stats(&self.stats);
+        self.done.notify_waiters();
+        self.writable.notify_waiters();
+        items
+    }
+
+    /// Close the queue, marking its connection as broken.
+    pub(super) fn fail(&self) {
+        self.state.lock().unwrap().failed = true;
+        self.close();
+    }
+
+    pub(super) fn is_failed(&self) -> bool {
+        self.state.lock().unwrap().failed
+    }
+
+    pub(super) fn is_closed(&self) -> bool {
+        self.state.lock().unwrap().closed
+    }
+}
+
+/// Queued item, along with its properties computed once on pushing
+struct Entry<T> {
+    item: T,
+    queued: Instant,
+    size: usize,
+    timestamp: Option<u64>,
+}
+
+impl<T> Entry<T>
+where
+    T: QueueItem,
+{
+    fn new(item: T) -> Self {
+        Self {
+            queued: Instant::now(),
+            size: item.size(),
+            timestamp: item.timestamp(),
+            item,
+        }
+    }
+}
+
+struct State<T> {
+    bytes: usize,
+    closed: bool,
+    failed: bool,
+    items: VecDeque<Entry<T>>,
+    unfinished: usize,
+}
+
+impl<T> State<T> {
+    fn is_full(&self, limits: &QueueLimits, entry: &Entry<T>) -> bool {
+        let front = match self.items.front() {
+            Some(front) => front,
+            // an empty queue always accepts an item
+            None => return false,
+        };
+
+        let max_size_buffers = limits.max_size_buffers as usize;
+        let max_size_bytes = limits.max_size_bytes as usize;
+        let max_size_time = limits.max_size_time;
+
+        (max_size_buffers > 0 && self.items.len() >= max_size_buffers)
+            || (max_size_bytes > 0 && self.bytes + entry.size > max_size_bytes)
+            || (max_size_time > 0
+                && matches!(
+                    (front.timestamp, entry.timestamp),
+                    (Some(first), Some(last)) if last.saturating_sub(first) >= max_size_time
+                ))
+    }
+
+    fn push(&mut self, entry: Entry<T>) {
+        self.bytes += entry.size;
+        self.unfinished += 1;
+        self.items.push_back(entry);
+    }
+
+    fn pop(&mut self) -> Option<Entry<T>> {
+        let entry = self.items.pop_front()?;
+        self.bytes -= entry.size;
+        Some(entry)
+    }
+
+    fn update_stats(&self, stats: &Stats) {
+        stats.set_queue_level(self.items.len(), self.bytes);
+    }
+}
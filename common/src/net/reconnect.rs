@@ -0,0 +1,46 @@
+use std::time::Duration;
+
+use rand::Rng;
+
+use crate::args::ReconnectArgs;
+
+/// Exponential backoff state of a reconnecting queue
+#[derive(Debug, Default)]
+pub(super) struct Backoff {
+    attempts: u32,
+}
+
+impl Backoff {
+    /// Number of the consecutive failed attempts
+    #[inline]
+    pub(super) const fn attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    /// Returns the delay of the next attempt,
+    /// or `None` if the max. number of retries is exceeded.
+    pub(super) fn next_delay(&mut self, args: &ReconnectArgs) -> Option<Duration> {
+        if args.max_retries >= 0 && self.attempts >= args.max_retries as u32 {
+            return None;
+        }
+
+        let backoff = (args.min_backoff as u64)
+            .saturating_mul(1 << self.attempts.min(32))
+            .min(args.max_backoff.max(args.min_backoff) as u64);
+        self.attempts += 1;
+
+        let jitter = args.jitter.clamp(0.0, 1.0);
+        let scale = if jitter > 0.0 {
+            1.0 + ::rand::thread_rng().gen_range(-jitter..=jitter)
+        } else {
+            1.0
+        };
+        Some(Duration::from_millis((backoff as f64 * scale) as u64))
+    }
+
+    /// Resets the state, returning whether it was reconnecting.
+    #[inline]
+    pub(super) fn reset(&mut self) -> bool {
+        ::core::mem::take(&mut self.attempts) > 0
+    }
+}
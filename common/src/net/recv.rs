@@ -1,19 +1,20 @@
-use std::time::Duration;
+use std::sync::Arc;
 
-use bytes::Bytes;
-use dash_pipe_provider::messengers::Subscriber;
-use gst::{error, glib::subclass::types::ObjectSubclassExt, DebugCategory, FlowError};
-use tokio::{
-    sync::mpsc::{self, error::SendTimeoutError},
-    task::JoinHandle,
+use dash_pipe_provider::{messengers::Subscriber, PipeMessage};
+use gst::{
+    debug, error, glib::subclass::types::ObjectSubclassExt, warning, DebugCategory, FlowError,
 };
+use tokio::task::JoinHandle;
 
-use crate::plugin::PluginImpl;
+use crate::{control::ControlMessage, plugin::PluginImpl};
+
+use super::queue::BoundedQueue;
 
 pub(super) struct Queue {
     cat: DebugCategory,
+    model: String,
     producer: JoinHandle<()>,
-    rx: mpsc::Receiver<Bytes>,
+    queue: Arc<BoundedQueue<PipeMessage>>,
 }
 
 impl Queue {
@@ -25,54 +26,87 @@ impl Queue {
             .call_client(|client, model| async { client.subscribe(model).await })
             .await?;
 
-        let super::QueueArgs { imp, .. } = args;
+        let super::QueueArgs {
+            imp,
+            limits,
+            model,
+            stats,
+            ..
+        } = args;
         let cat = imp.cat();
         let runtime = imp.runtime();
 
-        let (tx, rx) = mpsc::channel(4);
+        let queue = Arc::new(BoundedQueue::new(limits, stats.clone()));
         Ok(Self {
             cat,
-            producer: runtime.spawn(async move {
-                loop {
-                    match subscriber.read_one().await {
-                        Ok(Some(mut msg)) => {
-                            if let Some(data) = msg
-                                .payloads
-                                .pop()
-                                .and_then(|payload| payload.value().cloned())
-                            {
-                                match tx.send_timeout(data, Duration::from_millis(10)).await {
-                                    Ok(()) | Err(SendTimeoutError::Timeout(_)) => continue,
-                                    // Queue is destroying, stop sending.
-                                    Err(SendTimeoutError::Closed(_)) => break,
+            model,
+            producer: runtime.spawn({
+                let queue = queue.clone();
+                async move {
+                    loop {
+                        match subscriber.read_one().await {
+                            Ok(Some(msg)) => {
+                                if msg.payloads.iter().any(|payload| payload.value().is_some())
+                                    || ControlMessage::from_message(&msg).is_some()
+                                {
+                                    match queue.push(msg).await {
+                                        Ok(0) => continue,
+                                        Ok(dropped) => {
+                                            debug!(cat, "Dropped {dropped} message(s)");
+                                        }
+                                        // Queue is destroying, stop sending.
+                                        Err(_) => break,
+                                    }
                                 }
                             }
-                        }
-                        // Subscriber is destroying, stop sending.
-                        Ok(None) => break,
-                        Err(error) => {
-                            error!(cat, "Failed to receive data: {error}");
+                            // Subscriber is destroying, stop sending.
+                            Ok(None) => break,
+                            // Skip the malformed message, the subscriber is still fine.
+                            Err(error) if is_decode_error(&error) => {
+                                stats.on_decode_error();
+                                warning!(cat, "Skipped a malformed message: {error}");
+                            }
+                            Err(error) => {
+                                stats.on_subscribe_error();
+                                error!(cat, "Failed to receive data: {error}");
+
+                                // Subscriber is broken, let the channel reconnect.
+                                queue.fail();
+                                break;
+                            }
                         }
                     }
+                    queue.close();
                 }
             }),
-            rx,
+            queue,
         })
     }
 
     #[inline]
-    pub(super) async fn recv(&mut self) -> Option<Bytes> {
-        self.rx.recv().await
+    pub(super) async fn recv(&mut self) -> Option<PipeMessage> {
+        self.queue.pop().await
+    }
+
+    #[inline]
+    pub(super) fn model(&self) -> &str {
+        &self.model
+    }
+
+    #[inline]
+    pub(super) fn is_failed(&self) -> bool {
+        self.queue.is_failed()
     }
 
     pub(super) async fn stop(self, imp: &(impl ?Sized + PluginImpl)) {
         let Self {
             cat,
+            model: _,
             producer,
-            mut rx,
+            queue,
         } = self;
 
-        rx.close();
+        queue.close();
         producer.abort();
 
         if let Err(error) = producer.await {
@@ -80,3 +114,8 @@ impl Queue {
         }
     }
 }
+
+/// Whether the error is about a malformed message, rather than the transport
+fn is_decode_error(error: &::anyhow::Error) -> bool {
+    error.chain().any(|cause| cause.is::<::serde_json::Error>())
+}
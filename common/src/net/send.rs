@@ -1,14 +1,18 @@
-use dash_openapi::image::Image;
+use std::sync::Arc;
+
 use dash_pipe_provider::{messengers::Publisher, PipeMessage};
-use gst::{error, glib::subclass::types::ObjectSubclassExt, DebugCategory, FlowError};
-use tokio::{sync::mpsc, task::JoinHandle};
+use gst::{debug, error, glib::subclass::types::ObjectSubclassExt, DebugCategory, FlowError};
+use tokio::task::JoinHandle;
 
 use crate::plugin::PluginImpl;
 
+use super::queue::BoundedQueue;
+
 pub(super) struct Queue {
     cat: DebugCategory,
+    model: String,
     producer: JoinHandle<()>,
-    tx: mpsc::Sender<PipeMessage<Image>>,
+    queue: Arc<BoundedQueue<PipeMessage>>,
 }
 
 impl Queue {
@@ -20,49 +24,112 @@ impl Queue {
             .call_client(|client, model| async { client.publish(model).await })
             .await?;
 
-        let super::QueueArgs { imp, .. } = args;
+        let super::QueueArgs {
+            imp,
+            limits,
+            model,
+            stats,
+            ..
+        } = args;
         let cat = imp.cat();
         let runtime = imp.runtime();
 
-        let (tx, mut rx) = mpsc::channel(2);
+        let queue = Arc::new(BoundedQueue::new(limits, stats.clone()));
         Ok(Self {
             cat,
-            producer: runtime.spawn(async move {
-                while let Some(data) = rx.recv().await {
-                    if let Err(error) =
-                        Publisher::<_, PipeMessage<Image>>::send_one(&publisher, data).await
-                    {
-                        error!(cat, "Failed to send data: {error}");
+            model,
+            producer: runtime.spawn({
+                let queue = queue.clone();
+                async move {
+                    while let Some(data) = queue.pop().await {
+                        // keep the message to retry it on failure
+                        let result =
+                            Publisher::<_, PipeMessage>::send_one(&publisher, data.clone()).await;
+
+                        if let Err(error) = result {
+                            stats.on_publish_error();
+                            error!(cat, "Failed to send data: {error}");
+
+                            // Publisher is broken, let the channel reconnect and retry.
+                            queue.requeue(data);
+                            queue.fail();
+                            break;
+                        }
+                        queue.task_done();
                     }
                 }
             }),
-            tx,
+            queue,
         })
     }
 
+    #[inline]
+    pub(super) fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// Share the queue, e.g. to publish from a timer.
+    #[inline]
+    pub(super) fn sender(&self) -> Arc<BoundedQueue<PipeMessage>> {
+        self.queue.clone()
+    }
+
+    /// Take the messages left unpublished, e.g. by a broken publisher.
+    #[inline]
+    pub(super) fn take_backlog(&self) -> Vec<PipeMessage> {
+        self.queue.drain()
+    }
+
+    #[inline]
+    pub(super) fn is_failed(&self) -> bool {
+        self.queue.is_failed()
+    }
+
+    /// Wait until all the queued messages are published.
+    #[inline]
+    pub(super) async fn flush(&self) {
+        self.queue.join().await
+    }
+
     pub(super) async fn send(
         &self,
         imp: &(impl ?Sized + PluginImpl),
-        data: PipeMessage<Image>,
+        data: PipeMessage,
     ) -> Result<(), FlowError> {
-        self.tx.send(data).await.map_err(|error| {
-            error!(
-                self.cat,
-                imp: imp,
-                "{error}",
-            );
-            FlowError::Eos
-        })
+        match self.queue.push(data).await {
+            Ok(0) => Ok(()),
+            Ok(dropped) => {
+                debug!(
+                    self.cat,
+                    imp: imp,
+                    "Dropped {dropped} message(s)",
+                );
+                Ok(())
+            }
+            Err(_) => {
+                error!(
+                    self.cat,
+                    imp: imp,
+                    "Sender is closed",
+                );
+                Err(FlowError::Eos)
+            }
+        }
     }
 
     pub(super) async fn stop(self, imp: &(impl ?Sized + PluginImpl)) {
-        let Self { cat, producer, tx } = self;
+        let Self {
+            cat,
+            model: _,
+            producer,
+            queue,
+        } = self;
 
+        queue.close();
         producer.abort();
 
         if let Err(error) = producer.await {
             error!(cat, imp: imp, "Failed to stop sender: {error}");
         }
-        drop(tx);
     }
 }
@@ -0,0 +1,108 @@
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+use gst::Structure;
+
+/// Message counters of a channel, shared with its queues
+#[derive(Debug, Default)]
+pub struct Stats {
+    messages_in: AtomicU64,
+    messages_out: AtomicU64,
+    bytes_in: AtomicU64,
+    bytes_out: AtomicU64,
+    dropped: AtomicU64,
+    decode_errors: AtomicU64,
+    publish_errors: AtomicU64,
+    subscribe_errors: AtomicU64,
+    queue_messages: AtomicU64,
+    queue_bytes: AtomicU64,
+    queue_time_total: AtomicU64,
+    queue_time_count: AtomicU64,
+}
+
+impl Stats {
+    /// Name of the stats structure, also used for the element messages
+    pub const NAME: &'static str = "ark-stats";
+
+    pub(super) fn on_push(&self, size: usize) {
+        self.messages_in.fetch_add(1, Ordering::Relaxed);
+        self.bytes_in.fetch_add(size as u64, Ordering::Relaxed);
+    }
+
+    pub(super) fn on_pop(&self, size: usize, queue_time: Duration) {
+        self.messages_out.fetch_add(1, Ordering::Relaxed);
+        self.bytes_out.fetch_add(size as u64, Ordering::Relaxed);
+        self.queue_time_total
+            .fetch_add(queue_time.as_nanos() as u64, Ordering::Relaxed);
+        self.queue_time_count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(super) fn on_drop(&self, count: usize) {
+        self.dropped.fetch_add(count as u64, Ordering::Relaxed);
+    }
+
+    pub(super) fn on_decode_error(&self) {
+        self.decode_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(super) fn on_subscribe_error(&self) {
+        self.subscribe_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(super) fn on_publish_error(&self) {
+        self.publish_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(super) fn set_queue_level(&self, messages: usize, bytes: usize) {
+        self.queue_messages
+            .store(messages as u64, Ordering::Relaxed);
+        self.queue_bytes.store(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub(super) fn reset(&self) {
+        for counter in [
+            &self.messages_in,
+            &self.messages_out,
+            &self.bytes_in,
+            &self.bytes_out,
+            &self.dropped,
+            &self.decode_errors,
+            &self.publish_errors,
+            &self.subscribe_errors,
+            &self.queue_messages,
+            &self.queue_bytes,
+            &self.queue_time_total,
+            &self.queue_time_count,
+        ] {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
+
+    /// Average time spent by the messages in the queue, in ns
+    pub fn average_queue_time(&self) -> u64 {
+        match self.queue_time_count.load(Ordering::Relaxed) {
+            0 => 0,
+            count => self.queue_time_total.load(Ordering::Relaxed) / count,
+        }
+    }
+
+    pub fn to_structure(&self) -> Structure {
+        let load = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+
+        Structure::builder(Self::NAME)
+            .field("messages-in", load(&self.messages_in))
+            .field("messages-out", load(&self.messages_out))
+            .field("bytes-in", load(&self.bytes_in))
+            .field("bytes-out", load(&self.bytes_out))
+            .field("dropped", load(&self.dropped))
+            .field("decode-errors", load(&self.decode_errors))
+            .field("publish-errors", load(&self.publish_errors))
+            .field("subscribe-errors", load(&self.subscribe_errors))
+            .field("queue-level-messages", load(&self.queue_messages))
+            .field("queue-level-bytes", load(&self.queue_bytes))
+            .field("average-queue-time", self.average_queue_time())
+            .build()
+    }
+}
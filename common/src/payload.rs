@@ -0,0 +1,318 @@
+use dash_pipe_provider::DynValue;
+use gst::{
+    glib::{self, value::FromValue, Value},
+    Caps, CapsRef, Fraction, StructureRef,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::media::MediaType;
+
+/// Payload kind, inferred from the negotiated caps
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum PayloadKind {
+    Audio,
+    #[default]
+    Binary,
+    Jpeg,
+    Json,
+    Png,
+    Text,
+    Video,
+}
+
+impl PayloadKind {
+    pub fn from_caps(caps: &CapsRef) -> Self {
+        match caps.structure(0).map(|s| s.name().as_str()) {
+            Some("application/x-json") => Self::Json,
+            Some("image/jpeg") => Self::Jpeg,
+            Some("image/png") => Self::Png,
+            Some("text/x-raw") => Self::Text,
+            Some("video/x-raw") => Self::Video,
+            Some(name) if name.starts_with("audio/") => Self::Audio,
+            _ => Self::Binary,
+        }
+    }
+
+    /// Parse the payload reference stored in the message value
+    ///
+    /// Note that both `image/jpeg` and `image/png` payloads share the prefix,
+    /// so that [`PayloadKind::Jpeg`] is returned for them.
+    pub fn from_data(data: &str) -> Option<(Self, &str)> {
+        [
+            Self::Audio,
+            Self::Binary,
+            Self::Jpeg,
+            Self::Json,
+            Self::Text,
+            Self::Video,
+        ]
+        .into_iter()
+        .find_map(|kind| data.strip_prefix(kind.prefix()).map(|key| (kind, key)))
+    }
+
+    /// Prefix of the payload reference stored in the message value
+    pub const fn prefix(&self) -> &'static str {
+        match self {
+            Self::Audio => "@data:audio,",
+            Self::Binary => "@data:binary,",
+            Self::Jpeg | Self::Png => "@data:image,",
+            Self::Json => "@data:json,",
+            Self::Text => "@data:text,",
+            Self::Video => "@data:video,",
+        }
+    }
+
+    /// Build a message value which refers to the payload `key`
+    pub fn build_value(&self, key: &str, caps: Option<&CapsRef>) -> DynValue {
+        let data = format!("{prefix}{key}", prefix = self.prefix());
+        let mime = caps
+            .map(MediaType::from_caps)
+            .unwrap_or(&MediaType::FALLBACK)
+            .mime
+            .to_string();
+        let s = caps.and_then(|caps| caps.structure(0));
+
+        match self {
+            Self::Audio => to_value(AudioValue {
+                data,
+                mime,
+                format: s.and_then(|s| field(s, "format")),
+                rate: s.and_then(|s| field(s, "rate")),
+                channels: s.and_then(|s| field(s, "channels")),
+            }),
+            Self::Binary => to_value(BinaryValue { data, mime }),
+            Self::Jpeg | Self::Png => to_value(ImageValue {
+                data,
+                format: if *self == Self::Jpeg { "jpeg" } else { "png" }.into(),
+                width: s.and_then(|s| field(s, "width")),
+                height: s.and_then(|s| field(s, "height")),
+            }),
+            Self::Json => to_value(JsonValue { data }),
+            Self::Text => to_value(TextValue {
+                data,
+                format: s.and_then(|s| field(s, "format")),
+            }),
+            Self::Video => to_value(VideoValue {
+                data,
+                format: s.and_then(|s| field(s, "format")),
+                width: s.and_then(|s| field(s, "width")),
+                height: s.and_then(|s| field(s, "height")),
+                framerate: s
+                    .and_then(|s| s.get::<Fraction>("framerate").ok())
+                    .map(|framerate| framerate.to_string()),
+            }),
+        }
+    }
+}
+
+/// How to produce the payloads of a received message
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, glib::Enum)]
+#[repr(u32)]
+#[enum_type(name = "GsArkPayloadMode")]
+pub enum PayloadMode {
+    #[default]
+    #[enum_value(name = "Push the last payload only", nick = "last")]
+    Last,
+    #[enum_value(name = "Push each payload as its own buffer", nick = "buffers")]
+    Buffers,
+    #[enum_value(name = "Push all payloads as buffer lists, split by caps", nick = "list")]
+    List,
+}
+
+/// Merge the `source` value into the `target` one, overwriting the conflicting fields
+pub fn merge_value(target: &mut DynValue, source: DynValue) {
+    match (target, source) {
+        (DynValue::Object(target), DynValue::Object(source)) => {
+            for (key, value) in source {
+                merge_value(target.entry(key).or_insert(DynValue::Null), value);
+            }
+        }
+        (target, source) => *target = source,
+    }
+}
+
+/// Infer fixed caps of a payload from the message value, or from the payload key
+pub fn caps_from_message(value: &DynValue, key: &str) -> Option<Caps> {
+    caps_from_value(value, key)
+        .or_else(|| MediaType::from_key(key).map(MediaType::to_caps))
+        .filter(|caps| caps.is_fixed())
+}
+
+fn caps_from_value(value: &DynValue, payload_key: &str) -> Option<Caps> {
+    let data = value.get("data")?.as_str()?;
+    let (kind, key) = PayloadKind::from_data(data).filter(|(_, key)| *key == payload_key)?;
+
+    match kind {
+        PayloadKind::Audio => {
+            let AudioValue {
+                mime,
+                format,
+                rate,
+                channels,
+                ..
+            } = from_value(value)?;
+
+            let mut caps = MediaType::from_mime(&mime)?.to_caps();
+            if mime == "audio/pcm" {
+                set_field(&mut caps, "layout", Some("interleaved"));
+            }
+            set_field(&mut caps, "format", format);
+            set_field(&mut caps, "rate", rate);
+            set_field(&mut caps, "channels", channels);
+            Some(caps)
+        }
+        PayloadKind::Binary => {
+            let BinaryValue { mime, .. } = from_value(value)?;
+            MediaType::from_mime(&mime).map(MediaType::to_caps)
+        }
+        PayloadKind::Jpeg | PayloadKind::Png => {
+            let ImageValue {
+                format,
+                width,
+                height,
+                ..
+            } = from_value(value)?;
+
+            let mut caps = match format.as_str() {
+                "jpeg" => Caps::builder("image/jpeg").build(),
+                "png" => Caps::builder("image/png").build(),
+                _ => MediaType::from_key(key)?.to_caps(),
+            };
+            set_field(&mut caps, "width", width);
+            set_field(&mut caps, "height", height);
+            Some(caps)
+        }
+        PayloadKind::Json => Some(Caps::builder("application/x-json").build()),
+        PayloadKind::Text => {
+            let TextValue { format, .. } = from_value(value)?;
+            Some(
+                Caps::builder("text/x-raw")
+                    .field("format", format.as_deref().unwrap_or("utf8"))
+                    .build(),
+            )
+        }
+        PayloadKind::Video => {
+            let VideoValue {
+                format,
+                width,
+                height,
+                framerate,
+                ..
+            } = from_value(value)?;
+
+            let framerate = match framerate {
+                Some(framerate) => {
+                    let (numer, denom) = framerate.split_once('/')?;
+                    Fraction::new(numer.parse().ok()?, denom.parse().ok()?)
+                }
+                None => Fraction::new(0, 1),
+            };
+
+            let mut caps = Caps::builder("video/x-raw")
+                .field("framerate", framerate)
+                .build();
+            set_field(&mut caps, "format", format);
+            set_field(&mut caps, "width", width);
+            set_field(&mut caps, "height", height);
+            Some(caps)
+        }
+    }
+}
+
+/// Message value of `audio/*` payloads
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioValue {
+    pub data: String,
+    pub mime: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channels: Option<i32>,
+}
+
+/// Message value of unknown payloads
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BinaryValue {
+    pub data: String,
+    pub mime: String,
+}
+
+/// Message value of `image/jpeg` and `image/png` payloads
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageValue {
+    pub data: String,
+    pub format: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<i32>,
+}
+
+/// Message value of `application/x-json` payloads
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct JsonValue {
+    pub data: String,
+}
+
+/// Message value of `text/x-raw` payloads
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TextValue {
+    pub data: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+}
+
+/// Message value of `video/x-raw` payloads
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct VideoValue {
+    pub data: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub framerate: Option<String>,
+}
+
+fn field<T>(s: &StructureRef, name: &str) -> Option<T>
+where
+    T: for<'a> FromValue<'a>,
+{
+    s.get(name).ok()
+}
+
+fn set_field<T>(caps: &mut Caps, name: &str, value: Option<T>)
+where
+    T: Into<Value> + Send,
+{
+    if let Some(value) = value {
+        if let Some(s) = caps.make_mut().structure_mut(0) {
+            s.set(name, value);
+        }
+    }
+}
+
+fn from_value<T>(value: &DynValue) -> Option<T>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    T::deserialize(value).ok()
+}
+
+fn to_value<T>(value: T) -> DynValue
+where
+    T: Serialize,
+{
+    ::serde_json::to_value(value).expect("payload value should be serializable")
+}
@@ -1,10 +1,11 @@
+use once_cell::sync::OnceCell;
 use tokio::{runtime::Runtime, sync::RwLock};
 
-use crate::net::ChannelArgs;
+use crate::{net::ChannelArgs, runtime};
 
 pub struct BasePlugin<Args> {
     args: RwLock<Args>,
-    runtime: Runtime,
+    runtime: OnceCell<Option<Runtime>>,
 }
 
 impl<Args> Default for BasePlugin<Args>
@@ -12,12 +13,18 @@ where
     Args: Default,
 {
     fn default() -> Self {
-        let runtime = Runtime::new().expect("Tokio runtime should be created");
-        let _guard = runtime.enter();
-
         Self {
             args: RwLock::default(),
-            runtime,
+            runtime: OnceCell::default(),
+        }
+    }
+}
+
+impl<Args> Drop for BasePlugin<Args> {
+    fn drop(&mut self) {
+        // the element may be finalized by a task of its own runtime
+        if let Some(Some(runtime)) = self.runtime.take() {
+            runtime.shutdown_background();
         }
     }
 }
@@ -27,7 +34,15 @@ pub trait ArkSubclass {
 
     fn args(&self) -> &RwLock<<Self as ArkSubclass>::Args>;
 
+    /// Runtime of the element, or the shared one until started.
     fn runtime(&self) -> &Runtime;
+
+    /// Resolves the runtime of the element on starting, following the current args.
+    ///
+    /// It should not be called from within a runtime, as it waits for the args.
+    ///
+    /// The runtime is kept once resolved, as its tasks may outlive the element state.
+    fn init_runtime(&self) -> &Runtime;
 }
 
 impl<Args> ArkSubclass for BasePlugin<Args>
@@ -41,8 +56,21 @@ where
         &self.args
     }
 
-    #[inline]
     fn runtime(&self) -> &Runtime {
-        &self.runtime
+        self.runtime
+            .get()
+            .and_then(Option::as_ref)
+            .unwrap_or_else(runtime::shared)
+    }
+
+    fn init_runtime(&self) -> &Runtime {
+        self.runtime
+            .get_or_init(|| {
+                // called on starting, off the runtime workers
+                let dedicated = self.args.blocking_read().dedicated_runtime();
+                dedicated.then(runtime::dedicated)
+            })
+            .as_ref()
+            .unwrap_or_else(runtime::shared)
     }
 }
@@ -33,6 +33,11 @@ where
     fn runtime(&self) -> &Runtime {
         self.base.runtime()
     }
+
+    #[inline]
+    fn init_runtime(&self) -> &Runtime {
+        self.base.init_runtime()
+    }
 }
 
 impl<Args> ChannelSubclass for NetworkPlugin<Args>
@@ -0,0 +1,92 @@
+use std::{
+    env,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use once_cell::sync::{Lazy, OnceCell};
+use tokio::runtime::{Builder, Runtime};
+
+/// Tokio runtime configuration of the elements
+#[derive(Clone, Debug)]
+pub struct RuntimeConfig {
+    pub thread_name: String,
+    pub worker_threads: Option<usize>,
+}
+
+impl Default for RuntimeConfig {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            thread_name: Self::DEFAULT_THREAD_NAME.into(),
+            worker_threads: None,
+        }
+    }
+}
+
+impl RuntimeConfig {
+    pub const DEFAULT_THREAD_NAME: &'static str = "gsark-worker";
+
+    pub const ENV_THREAD_NAME: &'static str = "GSARK_RUNTIME_THREAD_NAME";
+    pub const ENV_WORKER_THREADS: &'static str = "GSARK_RUNTIME_WORKER_THREADS";
+
+    /// Loads the configuration from the environment variables,
+    /// falling back to the default values.
+    pub fn from_env() -> Self {
+        let default = Self::default();
+
+        Self {
+            thread_name: env::var(Self::ENV_THREAD_NAME)
+                .ok()
+                .filter(|name| !name.is_empty())
+                .unwrap_or(default.thread_name),
+            worker_threads: env::var(Self::ENV_WORKER_THREADS)
+                .ok()
+                .and_then(|threads| threads.parse().ok())
+                .filter(|&threads| threads > 0)
+                .or(default.worker_threads),
+        }
+    }
+
+    fn build(&self, thread_name: String) -> Runtime {
+        let mut builder = Builder::new_multi_thread();
+        builder.enable_all().thread_name(thread_name);
+        if let Some(threads) = self.worker_threads {
+            builder.worker_threads(threads);
+        }
+        builder.build().expect("Tokio runtime should be created")
+    }
+}
+
+static CONFIG: OnceCell<RuntimeConfig> = OnceCell::new();
+
+/// Overrides the runtime configuration of the plugin.
+///
+/// It should be called before any element is started,
+/// otherwise the given configuration is returned back.
+pub fn configure(config: RuntimeConfig) -> Result<(), RuntimeConfig> {
+    CONFIG.set(config)
+}
+
+/// Returns the runtime configuration of the plugin.
+pub fn config() -> &'static RuntimeConfig {
+    CONFIG.get_or_init(RuntimeConfig::from_env)
+}
+
+/// Returns the process-wide runtime, shared by all elements.
+pub fn shared() -> &'static Runtime {
+    static RUNTIME: Lazy<Runtime> = Lazy::new(|| {
+        let config = config();
+        config.build(config.thread_name.clone())
+    });
+
+    &RUNTIME
+}
+
+/// Creates a runtime dedicated to an element.
+pub fn dedicated() -> Runtime {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let config = config();
+    let index = COUNTER.fetch_add(1, Ordering::Relaxed);
+    config.build(format!("{}-{index}", config.thread_name))
+}
@@ -0,0 +1,61 @@
+use std::str::FromStr;
+
+use dash_pipe_provider::DynValue;
+use gst::{TagList, TagListRef, TagScope};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Stream identity and tags, carried along with the payloads on change
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct StreamInfo {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stream_id: Option<String>,
+    /// Stream-scoped tag list, serialized by GStreamer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tags: Option<String>,
+    /// Global-scoped tag list, serialized by GStreamer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub global_tags: Option<String>,
+}
+
+impl StreamInfo {
+    /// Field name of the stream info in the message value
+    pub const KEY: &'static str = "stream";
+
+    pub fn from_value(value: &DynValue) -> Option<Self> {
+        value
+            .get(Self::KEY)
+            .and_then(|info| Self::deserialize(info).ok())
+    }
+
+    pub fn insert_into(&self, value: &mut DynValue) {
+        if let Some(value) = value.as_object_mut() {
+            let info = ::serde_json::to_value(self).expect("stream info should be serializable");
+            value.insert(Self::KEY.into(), info);
+        }
+    }
+
+    /// Store the tag list into the field of its scope.
+    pub fn set_tags(&mut self, tags: &TagListRef) {
+        let serialized = Some(tags.to_string());
+        match tags.scope() {
+            TagScope::Global => self.global_tags = serialized,
+            _ => self.tags = serialized,
+        }
+    }
+
+    /// Parse the stored tag lists, in the order of global and stream scopes.
+    pub fn tag_lists(&self) -> impl Iterator<Item = TagList> + '_ {
+        [
+            (TagScope::Global, self.global_tags.as_deref()),
+            (TagScope::Stream, self.tags.as_deref()),
+        ]
+        .into_iter()
+        .filter_map(|(scope, tags)| {
+            let mut tags = TagList::from_str(tags?).ok()?;
+            tags.make_mut().set_scope(scope);
+            Some(tags)
+        })
+    }
+}
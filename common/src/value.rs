@@ -1,6 +1,7 @@
 use std::fmt;
 
 use gst::{
+    error,
     glib::{subclass::types::ObjectSubclassExt, value::FromValue, Value},
     info,
 };
@@ -20,3 +21,50 @@ where
     );
     *field = value;
 }
+
+/// Same as [`set_value`], without logging the values, e.g. of credentials.
+pub fn set_secret_value<'a, P, T>(plugin: &P, name: &str, field: &mut T, value: &'a Value)
+where
+    P: ObjectSubclassExt + PluginImpl,
+    T: FromValue<'a>,
+{
+    let value = value.get().expect("type checked upstream");
+    info!(
+        plugin.cat(),
+        imp: plugin,
+        "Changing {name}",
+    );
+    *field = value;
+}
+
+pub fn try_set_value<'a, P, T, R, E>(
+    plugin: &P,
+    name: &str,
+    field: &mut R,
+    value: &'a Value,
+    parse: impl FnOnce(T) -> Result<R, E>,
+) where
+    P: ObjectSubclassExt + PluginImpl,
+    T: FromValue<'a>,
+    R: fmt::Debug,
+    E: fmt::Display,
+{
+    let value = value.get().expect("type checked upstream");
+    match parse(value) {
+        Ok(value) => {
+            info!(
+                plugin.cat(),
+                imp: plugin,
+                "Changing {name} from {field:?} to {value:?}",
+            );
+            *field = value;
+        }
+        Err(error) => {
+            error!(
+                plugin.cat(),
+                imp: plugin,
+                "Failed to change {name}: {error}",
+            );
+        }
+    }
+}
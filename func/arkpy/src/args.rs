@@ -2,7 +2,7 @@ use std::path::PathBuf;
 
 use dash_pipe_function_python_provider::FunctionArgs;
 use gsark_common::{
-    args::Params,
+    args::{ClientArgs, Params},
     net::ChannelArgs,
     plugin::{base::ArkSubclass, PluginImpl},
     value::set_value,
@@ -38,6 +38,16 @@ impl ChannelArgs for Args {
         self.common.model()
     }
 
+    #[inline]
+    fn client(&self) -> &ClientArgs {
+        self.common.client()
+    }
+
+    #[inline]
+    fn dedicated_runtime(&self) -> bool {
+        self.common.dedicated_runtime()
+    }
+
     #[inline]
     fn otlp(&self) -> bool {
         self.common.otlp()
@@ -8,7 +8,7 @@ use dash_pipe_provider::{
     RemoteFunction,
 };
 use gsark_common::{
-    net::try_init_client,
+    net::{ChannelArgs, ClientPool},
     plugin::{
         base::{ArkSubclass, BasePlugin},
         PluginImpl,
@@ -37,7 +37,7 @@ use crate::args::Args;
 #[derive(Default)]
 pub struct Plugin {
     base: BasePlugin<Args>,
-    client: Mutex<Option<PipeClient>>,
+    client: Mutex<Option<Arc<PipeClient>>>,
     function: Mutex<Option<DynFunction>>,
 }
 
@@ -70,6 +70,11 @@ impl ArkSubclass for Plugin {
     fn runtime(&self) -> &Runtime {
         self.base.runtime()
     }
+
+    #[inline]
+    fn init_runtime(&self) -> &Runtime {
+        self.base.init_runtime()
+    }
 }
 
 impl GstObjectImpl for Plugin {}
@@ -81,7 +86,9 @@ impl BaseTransformImpl for Plugin {
 
     #[inline]
     fn start(&self) -> Result<(), ErrorMessage> {
-        self.runtime().block_on(self.init_function()).map(|_| ())
+        self.init_runtime()
+            .block_on(self.init_function())
+            .map(|_| ())
     }
 
     #[inline]
@@ -152,12 +159,13 @@ impl BaseTransformImpl for Plugin {
 }
 
 impl Plugin {
-    async fn init_client(&self) -> Result<MappedMutexGuard<'_, PipeClient>, ErrorMessage> {
+    async fn init_client(&self) -> Result<MappedMutexGuard<'_, Arc<PipeClient>>, ErrorMessage> {
         let mut lock = self.client.lock().await;
         match lock.as_ref() {
             Some(_) => Ok(sync::mutex::unwrap_lock(lock)),
             None => {
-                lock.replace(try_init_client().await?);
+                let client = self.args().read().await.client().clone();
+                lock.replace(ClientPool::global().get(&client).await?);
                 Ok(sync::mutex::unwrap_lock(lock))
             }
         }
@@ -259,6 +267,9 @@ impl Plugin {
 
     async fn stop_function(&self) {
         self.function.lock().await.take();
+
+        // release the shared client
+        self.client.lock().await.take();
     }
 }
 
@@ -0,0 +1,268 @@
+use anyhow::{anyhow, Result};
+use glob::Pattern;
+use gsark_common::{
+    args::Params,
+    plugin::PluginImpl,
+    value::{set_value, try_set_value},
+};
+use gst::glib::{
+    subclass::object::{ObjectImpl, ObjectImplExt},
+    value::ToValue,
+    ParamSpec, ParamSpecBuilderExt, ParamSpecString, ParamSpecUInt, Value,
+};
+
+/// Plugin property value storage
+#[derive(Clone, Debug)]
+pub struct Args {
+    max_pads: u32,
+    pads: Vec<(String, Pattern)>,
+}
+
+impl Default for Args {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            max_pads: 16,
+            pads: Default::default(),
+        }
+    }
+}
+
+/// Src pad receiving a payload key
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PadGroup {
+    /// Pad name, without the `src_` prefix
+    pub name: String,
+    /// Pattern of the payload keys gathered into the pad
+    pub pattern: String,
+}
+
+impl Args {
+    /// Max. number of src pads, or 0 if unlimited
+    #[inline]
+    pub const fn max_pads(&self) -> u32 {
+        self.max_pads
+    }
+
+    /// Pad receiving the payload `key`
+    ///
+    /// The first matching pattern wins. The other keys are grouped by their directory
+    /// and extension (e.g. `sink_0/000001.jpg` into `sink_0_jpg`), as the per-frame keys
+    /// would otherwise get their own pads. The keys without extensions keep their own pads.
+    pub fn pad_group(&self, key: &str) -> PadGroup {
+        if let Some((name, pattern)) = self.pads.iter().find(|(_, pattern)| pattern.matches(key)) {
+            return PadGroup {
+                name: name.clone(),
+                pattern: pattern.to_string(),
+            };
+        }
+
+        let (dir, file) = match key.rsplit_once('/') {
+            Some((dir, file)) => (Some(dir), file),
+            None => (None, key),
+        };
+        let (name, pattern) = match file.rsplit_once('.') {
+            Some((_, ext)) => match dir {
+                Some(dir) => (format!("{dir}_{ext}"), format!("{dir}/*.{ext}")),
+                None => (ext.into(), format!("*.{ext}")),
+            },
+            None => (key.into(), key.into()),
+        };
+
+        PadGroup {
+            name: name
+                .chars()
+                .map(|c| match c {
+                    'A'..='Z' | 'a'..='z' | '0'..='9' | '-' | '.' | '_' => c,
+                    _ => '_',
+                })
+                .collect(),
+            pattern,
+        }
+    }
+
+    fn properties() -> &'static [ParamSpec] {
+        static PROPERTIES: Params = Params::new(|| Args::default().as_params());
+
+        PROPERTIES.as_ref()
+    }
+
+    fn property(&self, _id: usize, pspec: &ParamSpec) -> Value {
+        match pspec.name() {
+            "max-pads" => self.max_pads.to_value(),
+            "pads" => {
+                let pads = self
+                    .pads
+                    .iter()
+                    .map(|(name, pattern)| format!("{name}={pattern}"))
+                    .collect::<Vec<_>>();
+                Some(pads.join(","))
+                    .filter(|pads| !pads.is_empty())
+                    .to_value()
+            }
+            _ => unimplemented!(),
+        }
+    }
+
+    fn set_property(
+        &mut self,
+        plugin: &(impl ?Sized + PluginImpl),
+        _id: usize,
+        value: &Value,
+        pspec: &ParamSpec,
+    ) {
+        let name = pspec.name();
+        match name {
+            "max-pads" => set_value(plugin, name, &mut self.max_pads, value),
+            "pads" => try_set_value(
+                plugin,
+                name,
+                &mut self.pads,
+                value,
+                |value: Option<String>| parse_pads(value.as_deref().unwrap_or_default()),
+            ),
+            _ => unimplemented!(),
+        }
+    }
+
+    fn as_params(&self) -> Vec<ParamSpec> {
+        vec![
+            ParamSpecUInt::builder("max-pads")
+                .nick("Max Pads")
+                .blurb(
+                    "Max. number of src pads, dropping the payloads of the others (0 = unlimited)",
+                )
+                .default_value(self.max_pads)
+                .build(),
+            ParamSpecString::builder("pads")
+                .nick("Pads")
+                .blurb(
+                    "Comma-separated pad names and glob patterns of their payload keys \
+                    (e.g. \"image=*.jpg,mask=*.png\"), otherwise by directory and extension",
+                )
+                .default_value(None)
+                .build(),
+        ]
+    }
+}
+
+fn parse_pads(value: &str) -> Result<Vec<(String, Pattern)>> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|pad| !pad.is_empty())
+        .map(|pad| {
+            let (name, pattern) = pad
+                .split_once('=')
+                .ok_or_else(|| anyhow!("missing payload key pattern of pad: {pad}"))?;
+            let name = name.trim();
+            if name.is_empty() {
+                return Err(anyhow!("missing pad name of pattern: {pattern}"));
+            }
+            Ok((name.into(), Pattern::new(pattern.trim())?))
+        })
+        .collect()
+}
+
+/// Implementation of glib::Object virtual methods
+impl ObjectImpl for crate::plugin::Plugin {
+    fn constructed(&self) {
+        self.parent_constructed();
+        self.init_sink_pad();
+    }
+
+    #[inline]
+    fn properties() -> &'static [ParamSpec] {
+        Args::properties()
+    }
+
+    #[inline]
+    fn set_property(&self, id: usize, value: &Value, pspec: &ParamSpec) {
+        self.args()
+            .lock()
+            .unwrap()
+            .set_property(self, id, value, pspec)
+    }
+
+    #[inline]
+    fn property(&self, id: usize, pspec: &ParamSpec) -> Value {
+        self.args().lock().unwrap().property(id, pspec)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(pads: &str) -> Args {
+        Args {
+            pads: parse_pads(pads).unwrap(),
+            ..Default::default()
+        }
+    }
+
+    fn group(name: &str, pattern: &str) -> PadGroup {
+        PadGroup {
+            name: name.into(),
+            pattern: pattern.into(),
+        }
+    }
+
+    #[test]
+    fn pads_are_parsed_in_order() {
+        let pads = parse_pads(" image = *.jpg ,mask=*.png,").unwrap();
+        let pads: Vec<_> = pads
+            .iter()
+            .map(|(name, pattern)| (name.as_str(), pattern.as_str()))
+            .collect();
+        assert_eq!(pads, [("image", "*.jpg"), ("mask", "*.png")]);
+
+        assert!(parse_pads("").unwrap().is_empty());
+        assert!(parse_pads("image").is_err());
+        assert!(parse_pads("=*.jpg").is_err());
+        assert!(parse_pads("image=[").is_err());
+    }
+
+    #[test]
+    fn first_matching_pattern_wins() {
+        let args = args("image=sink_0/*.jpg,any=*");
+        assert_eq!(
+            args.pad_group("sink_0/000001.jpg"),
+            group("image", "sink_0/*.jpg"),
+        );
+        assert_eq!(args.pad_group("sink_1/000001.jpg"), group("any", "*"));
+    }
+
+    #[test]
+    fn other_keys_are_grouped_by_directory_and_extension() {
+        let args = Args::default();
+        assert_eq!(
+            args.pad_group("sink_0/000001.jpg"),
+            group("sink_0_jpg", "sink_0/*.jpg"),
+        );
+        assert_eq!(
+            args.pad_group("sink_0/000002.jpg"),
+            args.pad_group("sink_0/000001.jpg"),
+        );
+        assert_eq!(
+            args.pad_group("a/b/000001.png"),
+            group("a_b_png", "a/b/*.png")
+        );
+        assert_eq!(args.pad_group("000001.jpg"), group("jpg", "*.jpg"));
+        assert_eq!(args.pad_group("depth"), group("depth", "depth"));
+    }
+
+    #[test]
+    fn sanitized_names_keep_their_patterns_apart() {
+        let args = Args::default();
+        assert_eq!(
+            args.pad_group("cam 1/000001.jpg"),
+            group("cam_1_jpg", "cam 1/*.jpg"),
+        );
+
+        // the same pad name, to be reported as a collision
+        let other = args.pad_group("cam_1/000001.jpg");
+        assert_eq!(other.name, "cam_1_jpg");
+        assert_ne!(other.pattern, args.pad_group("cam 1/000001.jpg").pattern);
+    }
+}
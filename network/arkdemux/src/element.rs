@@ -0,0 +1,45 @@
+use gsark_common::element;
+use gst::{
+    subclass::{
+        prelude::{ElementImpl, ElementImplExt},
+        ElementMetadata,
+    },
+    PadTemplate, StateChange, StateChangeError, StateChangeSuccess,
+};
+use once_cell::sync::Lazy;
+
+impl ElementImpl for crate::plugin::Plugin {
+    fn metadata() -> Option<&'static ElementMetadata> {
+        static ELEMENT_METADATA: Lazy<ElementMetadata> = Lazy::new(|| {
+            ElementMetadata::new(
+                crate::metadata::LONG_NAME,
+                crate::metadata::CLASS,
+                crate::metadata::DESCRIPTION,
+                crate::metadata::AUTHORS,
+            )
+        });
+
+        Some(&*ELEMENT_METADATA)
+    }
+
+    fn pad_templates() -> &'static [PadTemplate] {
+        static PAD_TEMPLATES: Templates =
+            Templates::new(|| vec![element::sink_dynamic(), element::src_sometimes()]);
+
+        PAD_TEMPLATES.as_ref()
+    }
+
+    fn change_state(
+        &self,
+        transition: StateChange,
+    ) -> Result<StateChangeSuccess, StateChangeError> {
+        let success = self.parent_change_state(transition)?;
+
+        if transition == StateChange::PausedToReady {
+            self.reset();
+        }
+        Ok(success)
+    }
+}
+
+type Templates = Lazy<Vec<PadTemplate>>;
@@ -0,0 +1,339 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use dash_pipe_provider::PipeMessage;
+use gsark_common::{
+    buffer::BufferInfo, media::MediaType, meta::ArkMessageMeta, net::BatchItem, payload,
+    plugin::PluginImpl,
+};
+use gst::{
+    debug, element_imp_error, error, event,
+    glib::{
+        self,
+        subclass::types::{ObjectSubclass, ObjectSubclassExt},
+    },
+    prelude::{ElementClassExt, ElementExt, ElementExtManual, GstObjectExt, PadExt, PadExtManual},
+    subclass::prelude::{ElementImplExt, GstObjectImpl},
+    warning, Buffer, Caps, ClockTime, DebugCategory, Event, EventView, FlowError, FlowSuccess,
+    FormattedSegment, Pad, PadBuilder, StreamError,
+};
+use gst_base::UniqueFlowCombiner;
+
+use crate::args::Args;
+
+/// Struct containing all the element data
+pub struct Plugin {
+    args: Mutex<Args>,
+    flow_combiner: Mutex<UniqueFlowCombiner>,
+    sinkpad: Pad,
+    state: Mutex<State>,
+}
+
+/// Src pads exposed so far
+#[derive(Default)]
+struct State {
+    /// Whether no more pads are going to be added
+    no_more_pads: bool,
+    /// Src pads by name, along with the payload key patterns they stand for
+    pads: HashMap<String, (Pad, String)>,
+}
+
+/// This trait registers our type with the GObject object system and
+/// provides the entry points for creating a new instance and setting
+/// up the class data
+#[glib::object_subclass]
+impl ObjectSubclass for Plugin {
+    const NAME: &'static str = crate::metadata::CLASS_NAME;
+    type Type = super::Plugin;
+    type ParentType = gst::Element;
+
+    fn with_class(klass: &Self::Class) -> Self {
+        let templ = klass.pad_template("sink").unwrap();
+        let sinkpad = PadBuilder::<Pad>::from_template(&templ)
+            .chain_function(|pad, parent, buffer| {
+                Self::catch_panic_pad_function(
+                    parent,
+                    || Err(FlowError::Error),
+                    |imp| imp.chain(pad, buffer),
+                )
+            })
+            .event_function(|pad, parent, event| {
+                Self::catch_panic_pad_function(parent, || false, |imp| imp.sink_event(pad, event))
+            })
+            .build();
+
+        Self {
+            args: Default::default(),
+            flow_combiner: Default::default(),
+            sinkpad,
+            state: Default::default(),
+        }
+    }
+}
+
+impl PluginImpl for Plugin {
+    #[inline]
+    fn cat(&self) -> DebugCategory {
+        *crate::CAT
+    }
+}
+
+impl GstObjectImpl for Plugin {}
+
+impl Plugin {
+    #[inline]
+    pub(crate) fn args(&self) -> &Mutex<Args> {
+        &self.args
+    }
+
+    pub(crate) fn init_sink_pad(&self) {
+        self.obj().add_pad(&self.sinkpad).unwrap();
+    }
+
+    /// Remove all the src pads, e.g. to start over a new stream.
+    pub(crate) fn reset(&self) {
+        let element = self.obj();
+        let pads: Vec<_> = {
+            let mut state = self.state.lock().unwrap();
+            state.no_more_pads = false;
+            state.pads.drain().map(|(_, (pad, _))| pad).collect()
+        };
+        for pad in pads {
+            if let Err(error) = pad.set_active(false) {
+                error!(
+                    self.cat(),
+                    imp: self,
+                    "Failed to deactivate {name}: {error}",
+                    name = pad.name(),
+                );
+            }
+            let _ = element.remove_pad(&pad);
+        }
+        self.flow_combiner.lock().unwrap().clear();
+    }
+
+    fn chain(&self, _pad: &Pad, buffer: Buffer) -> Result<FlowSuccess, FlowError> {
+        for (key, buffer, caps) in self.split(buffer)? {
+            let pad = match self.src_pad(&key, caps)? {
+                Some(pad) => pad,
+                None => continue,
+            };
+            let result = pad.push(buffer);
+            self.flow_combiner
+                .lock()
+                .unwrap()
+                .update_pad_flow(&pad, result)?;
+        }
+        Ok(FlowSuccess::Ok)
+    }
+
+    fn sink_event(&self, pad: &Pad, event: Event) -> bool {
+        match event.view() {
+            // each src pad starts its own stream with the payload caps
+            EventView::Caps(_) | EventView::StreamStart(_) => true,
+            EventView::Eos(_) if self.state.lock().unwrap().pads.is_empty() => {
+                element_imp_error!(
+                    self,
+                    StreamError::Demux,
+                    ["Received no payloads to demultiplex"]
+                );
+                false
+            }
+            EventView::Eos(_) => {
+                self.no_more_pads();
+                Pad::event_default(pad, Some(&*self.obj()), event)
+            }
+            EventView::FlushStop(_) => {
+                self.flow_combiner.lock().unwrap().reset();
+                Pad::event_default(pad, Some(&*self.obj()), event)
+            }
+            _ => Pad::event_default(pad, Some(&*self.obj()), event),
+        }
+    }
+
+    /// Split the buffer into the payloads, along with their keys and caps.
+    ///
+    /// The buffers carrying a message meta (e.g. from `arksrc`) are already split,
+    /// and the others are parsed as serialized messages.
+    fn split(&self, buffer: Buffer) -> Result<Vec<(String, Buffer, Option<Caps>)>, FlowError> {
+        if let Some(meta) = ArkMessageMeta::from_buffer(&buffer) {
+            let caps = self
+                .sinkpad
+                .current_caps()
+                .filter(|caps| caps.is_fixed())
+                .or_else(|| payload::caps_from_message(&meta.value, &meta.key));
+            return Ok(vec![(meta.key, buffer, caps)]);
+        }
+
+        let message: PipeMessage = {
+            let data = buffer.map_readable().map_err(|_| {
+                element_imp_error!(self, StreamError::Demux, ["Failed to map a buffer"]);
+                FlowError::Error
+            })?;
+            ::serde_json::from_slice(&data).map_err(|error| {
+                element_imp_error!(
+                    self,
+                    StreamError::Demux,
+                    ["Failed to parse an OpenARK message: {error}"]
+                );
+                FlowError::Error
+            })?
+        };
+
+        // restore timestamps and flags of each payload, or inherit the ones of the buffer
+        let count = message
+            .payloads
+            .iter()
+            .filter(|payload| payload.value().is_some())
+            .count();
+        let info = BufferInfo::from_value(&message.value)
+            .unwrap_or_else(|| BufferInfo::from_buffer(&buffer));
+        let items = BatchItem::from_value(&message.value).unwrap_or_default();
+
+        // keep the model of the message, if serialized along with the other message fields
+        let model = ArkMessageMeta::from_message("", "", &message)
+            .headers
+            .get("model")
+            .and_then(|model| model.as_str())
+            .unwrap_or_default()
+            .to_string();
+
+        Ok(message
+            .payloads
+            .iter()
+            .filter(|payload| payload.value().is_some())
+            .enumerate()
+            .map(|(index, payload)| {
+                let caps = payload::caps_from_message(&message.value, payload.key());
+                let info = match items.get(index).filter(|item| item.key == payload.key()) {
+                    Some(item) => item.buffer.clone(),
+                    None => Some(info.interpolate(index, count)),
+                };
+
+                let data = payload.value().cloned().unwrap();
+                let mut buffer = ::gsark_common::buffer::from_bytes(data);
+                {
+                    let buffer = buffer.make_mut();
+                    if let Some(info) = &info {
+                        info.apply(buffer);
+                    }
+
+                    // attach the message
+                    ArkMessageMeta::from_message(&model, payload.key(), &message).add(buffer);
+                }
+                (payload.key().into(), buffer, caps)
+            })
+            .collect())
+    }
+
+    /// Get the src pad of the payload `key`, creating it on the first payload.
+    ///
+    /// Returns `None` if the payload should be dropped, as the max. number of pads is reached.
+    fn src_pad(&self, key: &str, caps: Option<Caps>) -> Result<Option<Pad>, FlowError> {
+        let (group, max_pads) = {
+            let args = self.args.lock().unwrap();
+            (args.pad_group(key), args.max_pads())
+        };
+        let name = format!("src_{}", group.name);
+        let caps = caps.unwrap_or_else(|| MediaType::FALLBACK.to_caps());
+
+        let pad = {
+            let state = self.state.lock().unwrap();
+            match state.pads.get(&name) {
+                Some((pad, pattern)) if *pattern == group.pattern => Some(pad.clone()),
+                // the sanitized names may collide
+                Some((_, pattern)) => {
+                    element_imp_error!(
+                        self,
+                        StreamError::Demux,
+                        [
+                            "Payload keys {new} and {pattern} share the pad {name}, \
+                            consider setting the pads property",
+                            new = group.pattern,
+                        ]
+                    );
+                    return Err(FlowError::Error);
+                }
+                None if state.no_more_pads => {
+                    debug!(
+                        self.cat(),
+                        imp: self,
+                        "Dropped a payload {key} without pads",
+                    );
+                    return Ok(None);
+                }
+                None => None,
+            }
+        };
+        let pad = match pad {
+            Some(pad) => pad,
+            None => {
+                let pad = self.add_src_pad(&name, &caps)?;
+                let count = {
+                    let mut state = self.state.lock().unwrap();
+                    state.pads.insert(name, (pad.clone(), group.pattern));
+                    state.pads.len()
+                };
+                if max_pads > 0 && count >= max_pads as usize {
+                    warning!(
+                        self.cat(),
+                        imp: self,
+                        "Reached {max_pads} pads, dropping the payloads of the others",
+                    );
+                    self.no_more_pads();
+                }
+                pad
+            }
+        };
+
+        if pad.current_caps().as_ref() != Some(&caps) {
+            pad.push_event(event::Caps::new(&caps));
+        }
+        Ok(Some(pad))
+    }
+
+    /// Signal that no more pads are going to be added, if not yet.
+    fn no_more_pads(&self) {
+        let signalled = ::core::mem::replace(&mut self.state.lock().unwrap().no_more_pads, true);
+        if !signalled {
+            self.obj().no_more_pads();
+        }
+    }
+
+    fn add_src_pad(&self, name: &str, caps: &Caps) -> Result<Pad, FlowError> {
+        let element = self.obj();
+        let templ = element.pad_template("src_%s").unwrap();
+        let pad = PadBuilder::<Pad>::from_template(&templ).name(name).build();
+
+        pad.set_active(true).map_err(|error| {
+            error!(
+                self.cat(),
+                imp: self,
+                "Failed to activate {name}: {error}",
+            );
+            FlowError::Error
+        })?;
+
+        // send the sticky events before exposing the pad
+        let stream_id = pad.create_stream_id(&*element, Some(name));
+        pad.push_event(event::StreamStart::new(&stream_id));
+        pad.push_event(event::Caps::new(caps));
+        match self.sinkpad.sticky_event::<event::Segment>(0) {
+            Some(segment) => pad.push_event(Event::from(segment)),
+            None => {
+                let segment = FormattedSegment::<ClockTime>::new();
+                pad.push_event(event::Segment::new(&segment))
+            }
+        };
+
+        element.add_pad(&pad).map_err(|error| {
+            error!(
+                self.cat(),
+                imp: self,
+                "Failed to add {name}: {error}",
+            );
+            FlowError::Error
+        })?;
+        self.flow_combiner.lock().unwrap().add_pad(&pad);
+        Ok(pad)
+    }
+}
@@ -1,5 +1,178 @@
-use gsark_common::{args::Args, net::ChannelArgs, plugin::base::ArkSubclass};
-use gst::glib::{subclass::object::ObjectImpl, ParamSpec, Value};
+use anyhow::{bail, Result};
+use dash_pipe_provider::DynValue;
+use gsark_common::{
+    args::{ClientArgs, Params, QueueLimits, ReconnectArgs, StatsArgs},
+    net::{ChannelArgs, ChannelSubclass, ChannelSubclassExt},
+    plugin::{base::ArkSubclass, PluginImpl},
+    value::{set_value, try_set_value},
+};
+use gst::{
+    glib::{
+        subclass::{object::ObjectImpl, types::ObjectSubclassExt},
+        value::ToValue,
+        ParamSpec, ParamSpecBuilderExt, ParamSpecString, ParamSpecUInt, ParamSpecUInt64, Value,
+    },
+    prelude::{Cast, ElementExtManual},
+};
+
+use crate::{pad::SinkPad, template::KeyTemplate};
+
+/// Plugin property value storage
+#[derive(Clone, Debug)]
+pub struct Args {
+    batch_duration: u64,
+    batch_size: u32,
+    common: ::gsark_common::args::Args,
+    key_template: KeyTemplate,
+    queue: QueueLimits,
+    reconnect: ReconnectArgs,
+    start_index: u64,
+    stats: StatsArgs,
+    value: Option<DynValue>,
+}
+
+impl Default for Args {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            batch_duration: 0,
+            batch_size: 1,
+            common: Default::default(),
+            key_template: Default::default(),
+            queue: Default::default(),
+            reconnect: Default::default(),
+            start_index: Default::default(),
+            stats: Default::default(),
+            value: Default::default(),
+        }
+    }
+}
+
+impl ChannelArgs for Args {
+    #[inline]
+    fn model(&self) -> &String {
+        self.common.model()
+    }
+
+    #[inline]
+    fn client(&self) -> &ClientArgs {
+        self.common.client()
+    }
+
+    #[inline]
+    fn dedicated_runtime(&self) -> bool {
+        self.common.dedicated_runtime()
+    }
+
+    #[inline]
+    fn otlp(&self) -> bool {
+        self.common.otlp()
+    }
+
+    #[inline]
+    fn batch_duration(&self) -> u64 {
+        self.batch_duration
+    }
+
+    #[inline]
+    fn batch_size(&self) -> u32 {
+        self.batch_size
+    }
+
+    #[inline]
+    fn stats_interval(&self) -> u32 {
+        self.stats.interval
+    }
+
+    #[inline]
+    fn value(&self) -> Option<&DynValue> {
+        self.value.as_ref()
+    }
+
+    #[inline]
+    fn queue(&self) -> QueueLimits {
+        self.queue.clone()
+    }
+
+    #[inline]
+    fn reconnect(&self) -> ReconnectArgs {
+        self.reconnect.clone()
+    }
+
+    #[inline]
+    fn properties() -> &'static [ParamSpec] {
+        static PROPERTIES: Params = Params::new(|| Args::default().as_params());
+
+        PROPERTIES.as_ref()
+    }
+
+    #[inline]
+    fn property(&self, id: usize, pspec: &ParamSpec) -> Value {
+        match pspec.name() {
+            "batch-duration" => self.batch_duration.to_value(),
+            "batch-size" => self.batch_size.to_value(),
+            "key-template" => self.key_template.to_string().to_value(),
+            "start-index" => self.start_index.to_value(),
+            "value" => self
+                .value
+                .as_ref()
+                .map(|value| value.to_string())
+                .to_value(),
+            name if QueueLimits::is_property(name) => self.queue.property(id, pspec),
+            name if ReconnectArgs::is_property(name) => self.reconnect.property(id, pspec),
+            name if StatsArgs::is_property(name) => self.stats.property(id, pspec),
+            _ => self.common.property(id, pspec),
+        }
+    }
+
+    #[inline]
+    fn set_property(
+        &mut self,
+        plugin: &(impl ?Sized + PluginImpl),
+        id: usize,
+        value: &Value,
+        pspec: &ParamSpec,
+    ) {
+        let name = pspec.name();
+        match name {
+            "batch-duration" => set_value(plugin, name, &mut self.batch_duration, value),
+            "batch-size" => set_value(plugin, name, &mut self.batch_size, value),
+            "key-template" => try_set_value(
+                plugin,
+                name,
+                &mut self.key_template,
+                value,
+                |value: Option<String>| value.unwrap_or_default().parse(),
+            ),
+            "start-index" => set_value(plugin, name, &mut self.start_index, value),
+            "value" => try_set_value(
+                plugin,
+                name,
+                &mut self.value,
+                value,
+                |value: Option<String>| -> Result<_> {
+                    match value.as_deref() {
+                        None | Some("") => Ok(None),
+                        Some(value) => match ::serde_json::from_str(value)? {
+                            value @ DynValue::Object(_) => Ok(Some(value)),
+                            _ => bail!("value should be a JSON object"),
+                        },
+                    }
+                },
+            ),
+            name if QueueLimits::is_property(name) => {
+                self.queue.set_property(plugin, id, value, pspec)
+            }
+            name if ReconnectArgs::is_property(name) => {
+                self.reconnect.set_property(plugin, id, value, pspec)
+            }
+            name if StatsArgs::is_property(name) => {
+                self.stats.set_property(plugin, id, value, pspec)
+            }
+            _ => self.common.set_property(plugin, id, value, pspec),
+        }
+    }
+}
 
 /// Implementation of glib::Object virtual methods
 impl ObjectImpl for crate::plugin::Plugin {
@@ -12,11 +185,84 @@ impl ObjectImpl for crate::plugin::Plugin {
     fn set_property(&self, id: usize, value: &Value, pspec: &ParamSpec) {
         self.args()
             .blocking_write()
-            .set_property(self, id, value, pspec)
+            .set_property(self, id, value, pspec);
+
+        // the model can be switched while running
+        if pspec.name() == "model" {
+            self.spawn_switch_model();
+
+            // along with the request pads sharing it
+            for pad in self.obj().sink_pads() {
+                if let Ok(pad) = pad.downcast::<SinkPad>() {
+                    if pad.model().is_none() {
+                        self.spawn_switch_pad_model(&pad);
+                    }
+                }
+            }
+        }
     }
 
     #[inline]
     fn property(&self, id: usize, pspec: &ParamSpec) -> Value {
-        self.args().blocking_read().property(id, pspec)
+        match pspec.name() {
+            "stats" => self.channel().stats().to_structure().to_value(),
+            _ => self.args().blocking_read().property(id, pspec),
+        }
+    }
+}
+
+impl Args {
+    fn as_params(&self) -> Vec<ParamSpec> {
+        let mut params = self.common.as_params();
+        params.extend(self.queue.as_params());
+        params.extend(self.reconnect.as_params());
+        params.extend(self.stats.as_params());
+        params.push(
+            ParamSpecUInt64::builder("batch-duration")
+                .nick("Batch Duration")
+                .blurb("Max. span and delay of the buffers sent in a message, in ns (0=disable)")
+                .default_value(self.batch_duration)
+                .build(),
+        );
+        params.push(
+            ParamSpecUInt::builder("batch-size")
+                .nick("Batch Size")
+                .blurb("Max. number of buffers sent in a message")
+                .minimum(1)
+                .default_value(self.batch_size)
+                .build(),
+        );
+        params.push(
+            ParamSpecString::builder("key-template")
+                .nick("Key Template")
+                .blurb("Payload key template, with placeholders such as {index}, {pts}, {dts}, {running-time}, {wallclock}, {uuid}, {element-name}, {pad-name}, {stream-id} and {ext}")
+                .default_value(Some(KeyTemplate::DEFAULT))
+                .build(),
+        );
+        params.push(
+            ParamSpecUInt64::builder("start-index")
+                .nick("Start Index")
+                .blurb("Initial value of the per-stream {index} counter")
+                .default_value(self.start_index)
+                .build(),
+        );
+        params.push(
+            ParamSpecString::builder("value")
+                .nick("Value")
+                .blurb("Static JSON object merged into every message value")
+                .default_value(None)
+                .build(),
+        );
+        params
+    }
+
+    #[inline]
+    pub const fn key_template(&self) -> &KeyTemplate {
+        &self.key_template
+    }
+
+    #[inline]
+    pub const fn start_index(&self) -> u64 {
+        self.start_index
     }
 }
@@ -1,10 +1,19 @@
-use gsark_common::element;
+use gsark_common::{element, plugin::PluginImpl};
 use gst::{
-    subclass::{prelude::ElementImpl, ElementMetadata},
-    PadTemplate,
+    error,
+    glib::subclass::types::ObjectSubclassExt,
+    loggable_error,
+    prelude::{Cast, ElementExt, GstObjectExt, PadExt, StaticType},
+    subclass::{
+        prelude::{ElementImpl, ElementImplExt},
+        ElementMetadata,
+    },
+    Caps, FlowError, Pad, PadBuilder, PadTemplate,
 };
 use once_cell::sync::Lazy;
 
+use crate::pad::SinkPad;
+
 impl ElementImpl for crate::plugin::Plugin {
     fn metadata() -> Option<&'static ElementMetadata> {
         static ELEMENT_METADATA: Lazy<ElementMetadata> = Lazy::new(|| {
@@ -20,10 +29,84 @@ impl ElementImpl for crate::plugin::Plugin {
     }
 
     fn pad_templates() -> &'static [PadTemplate] {
-        static PAD_TEMPLATES: Templates = Templates::new(|| vec![element::sink_dynamic()]);
+        static PAD_TEMPLATES: Templates = Templates::new(|| {
+            vec![
+                element::sink_dynamic(),
+                element::sink_request(SinkPad::static_type()),
+            ]
+        });
 
         PAD_TEMPLATES.as_ref()
     }
+
+    fn request_new_pad(
+        &self,
+        templ: &PadTemplate,
+        name: Option<&str>,
+        _caps: Option<&Caps>,
+    ) -> Option<Pad> {
+        let element = self.obj();
+        let name = match name {
+            Some(name) => name.to_string(),
+            None => (0..)
+                .map(|index| format!("sink_{index}"))
+                .find(|name| element.static_pad(name).is_none())
+                .unwrap(),
+        };
+        if element.static_pad(&name).is_some() {
+            return None;
+        }
+
+        let pad = PadBuilder::<SinkPad>::from_template(templ)
+            .name(name)
+            .activatemode_function(|pad, parent, mode, active| {
+                Self::catch_panic_pad_function(
+                    parent,
+                    || {
+                        Err(loggable_error!(
+                            *crate::CAT,
+                            "Panic activating {}",
+                            pad.name(),
+                        ))
+                    },
+                    |imp| imp.activate_pad(pad, mode, active),
+                )
+            })
+            .chain_function(|pad, parent, buffer| {
+                Self::catch_panic_pad_function(
+                    parent,
+                    || Err(FlowError::Error),
+                    |imp| imp.chain_pad(pad, buffer),
+                )
+            })
+            .chain_list_function(|pad, parent, list| {
+                Self::catch_panic_pad_function(
+                    parent,
+                    || Err(FlowError::Error),
+                    |imp| imp.chain_list_pad(pad, list),
+                )
+            })
+            .event_function(|pad, parent, event| {
+                Self::catch_panic_pad_function(parent, || false, |imp| imp.event_pad(pad, event))
+            })
+            .build();
+
+        element.add_pad(&pad).ok()?;
+        Some(pad.upcast())
+    }
+
+    fn release_pad(&self, pad: &Pad) {
+        let element = self.obj();
+        if let Err(error) = pad.set_active(false) {
+            error!(
+                self.cat(),
+                imp: self,
+                "Failed to deactivate {name}: {error}",
+                name = pad.name(),
+            );
+        }
+        let _ = element.remove_pad(pad);
+    }
 }
 
 type Templates = Lazy<Vec<PadTemplate>>;
@@ -1,6 +1,8 @@
 mod args;
 mod element;
+mod pad;
 mod plugin;
+mod template;
 
 use gst::{glib, prelude::StaticType, DebugCategory, DebugColorFlags, Element, Object, Rank};
 use once_cell::sync::Lazy;
@@ -19,8 +21,7 @@ pub mod metadata {
 glib::wrapper! {
     pub struct Plugin(ObjectSubclass<plugin::Plugin>)
     @extends
-        gst_base::PushSrc,
-        gst_base::BaseSrc,
+        gst_base::BaseSink,
         Element,
         Object
     ;
@@ -30,6 +31,8 @@ glib::wrapper! {
 /// the name for being able to instantiate it via e.g.
 /// gst::ElementFactory::make().
 pub fn register(plugin: &gst::Plugin) -> Result<(), glib::BoolError> {
+    ::gsark_common::meta::ArkMessageMeta::register();
+
     Element::register(
         Some(plugin),
         crate::metadata::NAME,
@@ -0,0 +1,167 @@
+use std::sync::atomic::Ordering;
+
+use gsark_common::net::Channel;
+use gst::{
+    glib::{self, subclass::types::ObjectSubclassIsExt},
+    prelude::GstObjectExt,
+    Caps, ClockTime, FormattedSegment,
+};
+use tokio::sync::{Mutex, RwLock};
+
+use crate::plugin::Stream;
+
+// The public Rust wrapper type for our request pads
+glib::wrapper! {
+    pub struct SinkPad(ObjectSubclass<imp::SinkPad>)
+    @extends
+        gst::Pad,
+        gst::Object
+    ;
+}
+
+impl SinkPad {
+    /// OpenARK model name, if not shared with the element
+    pub fn model(&self) -> Option<String> {
+        self.imp().model.lock().unwrap().clone()
+    }
+
+    /// Stream ID sent along with every message, defaulting to the pad name
+    pub fn stream_id(&self) -> String {
+        match self.imp().stream_id.lock().unwrap().clone() {
+            Some(stream_id) => stream_id,
+            None => self.name().to_string(),
+        }
+    }
+
+    #[inline]
+    pub fn channel(&self) -> &Channel {
+        &self.imp().channel
+    }
+
+    #[inline]
+    pub(crate) fn caps(&self) -> &RwLock<Option<Caps>> {
+        &self.imp().caps
+    }
+
+    #[inline]
+    pub(crate) fn stream(&self) -> &Mutex<Stream> {
+        &self.imp().stream
+    }
+
+    pub(crate) fn running_time(&self, time: Option<ClockTime>) -> Option<ClockTime> {
+        self.imp().segment.lock().unwrap().to_running_time(time)
+    }
+
+    pub(crate) fn set_segment(&self, segment: FormattedSegment<ClockTime>) {
+        *self.imp().segment.lock().unwrap() = segment;
+    }
+
+    /// Marks the pad as ended, returning whether it was not yet.
+    pub(crate) fn set_eos(&self, eos: bool) -> bool {
+        self.imp().eos.swap(eos, Ordering::SeqCst) != eos
+    }
+
+    #[inline]
+    pub(crate) fn is_eos(&self) -> bool {
+        self.imp().eos.load(Ordering::SeqCst)
+    }
+}
+
+mod imp {
+    use std::sync::{atomic::AtomicBool, Mutex};
+
+    use gsark_common::net::Channel;
+    use gst::{
+        glib::{
+            self,
+            subclass::{
+                object::ObjectImpl,
+                types::{ObjectSubclass, ObjectSubclassIsExt},
+            },
+            value::ToValue,
+            ParamSpec, ParamSpecBuilderExt, ParamSpecString, Value,
+        },
+        prelude::{CastNone, GstObjectExt},
+        subclass::prelude::{GstObjectImpl, ObjectSubclassExt, PadImpl},
+        Caps, ClockTime, FormattedSegment,
+    };
+    use once_cell::sync::Lazy;
+    use tokio::sync::RwLock;
+
+    use crate::plugin::Stream;
+
+    /// Struct containing all the request pad data
+    #[derive(Default)]
+    pub struct SinkPad {
+        pub(super) caps: RwLock<Option<Caps>>,
+        pub(super) channel: Channel,
+        pub(super) eos: AtomicBool,
+        pub(super) model: Mutex<Option<String>>,
+        pub(super) segment: Mutex<FormattedSegment<ClockTime>>,
+        pub(super) stream: ::tokio::sync::Mutex<Stream>,
+        pub(super) stream_id: Mutex<Option<String>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for SinkPad {
+        const NAME: &'static str = "ArkSinkPad";
+        type Type = super::SinkPad;
+        type ParentType = gst::Pad;
+    }
+
+    impl ObjectImpl for SinkPad {
+        fn properties() -> &'static [ParamSpec] {
+            static PROPERTIES: Lazy<Vec<ParamSpec>> = Lazy::new(|| {
+                vec![
+                    ParamSpecString::builder("model")
+                        .nick("Model")
+                        .blurb("OpenARK model name, defaulting to the one of the element")
+                        .default_value(None)
+                        .build(),
+                    ParamSpecString::builder("stream-id")
+                        .nick("Stream ID")
+                        .blurb(
+                            "Stream ID sent along with every message, defaulting to the pad name",
+                        )
+                        .default_value(None)
+                        .build(),
+                ]
+            });
+
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &Value, pspec: &ParamSpec) {
+            let value = value
+                .get::<Option<String>>()
+                .expect("type checked upstream")
+                .filter(|value| !value.is_empty());
+
+            match pspec.name() {
+                "model" => {
+                    *self.model.lock().unwrap() = value;
+
+                    // the model can be switched while running
+                    let pad = self.obj();
+                    if let Some(element) = pad.parent().and_downcast::<crate::Plugin>() {
+                        element.imp().spawn_switch_pad_model(&pad);
+                    }
+                }
+                "stream-id" => *self.stream_id.lock().unwrap() = value,
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &ParamSpec) -> Value {
+            match pspec.name() {
+                "model" => self.model.lock().unwrap().to_value(),
+                "stream-id" => self.obj().stream_id().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+    }
+
+    impl GstObjectImpl for SinkPad {}
+
+    impl PadImpl for SinkPad {}
+}
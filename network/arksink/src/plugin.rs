@@ -1,24 +1,39 @@
-use std::sync::atomic::{AtomicU64, Ordering};
-
 use anyhow::Result;
 use gsark_common::{
-    args::Args,
+    media::MediaType,
     net::{Channel, ChannelSubclass, ChannelSubclassExt},
     plugin::{base::ArkSubclass, network::NetworkPlugin, PluginImpl},
+    stream::StreamInfo,
 };
 use gst::{
-    glib::{self, subclass::types::ObjectSubclass},
+    debug, error,
+    glib::{
+        self,
+        subclass::types::{ObjectSubclass, ObjectSubclassExt, ObjectSubclassIsExt},
+    },
+    loggable_error, message,
+    prelude::{Cast, ElementExt, ElementExtManual, GstObjectExt, ObjectExt, PadExt},
     subclass::prelude::GstObjectImpl,
-    Buffer, DebugCategory, ErrorMessage, FlowError, FlowSuccess,
+    Buffer, BufferList, Caps, ClockTime, DebugCategory, ErrorMessage, Event, EventView, FlowError,
+    FlowSuccess, LoggableError, PadMode, TagListRef,
+};
+use gst_base::{
+    prelude::{BaseSinkExt, BaseSinkExtManual},
+    subclass::prelude::{BaseSinkImpl, BaseSinkImplExt},
+};
+use tokio::{
+    runtime::Runtime,
+    sync::{Mutex, RwLock},
 };
-use gst_base::subclass::prelude::BaseSinkImpl;
-use tokio::{runtime::Runtime, sync::RwLock};
+
+use crate::{args::Args, pad::SinkPad, template::KeyContext};
 
 /// Struct containing all the element data
 #[derive(Default)]
 pub struct Plugin {
-    counter: AtomicU64,
+    caps: RwLock<Option<Caps>>,
     network: NetworkPlugin<Args>,
+    stream: Mutex<Stream>,
 }
 
 /// This trait registers our type with the GObject object system and
@@ -50,6 +65,11 @@ impl ArkSubclass for Plugin {
     fn runtime(&self) -> &Runtime {
         self.network.runtime()
     }
+
+    #[inline]
+    fn init_runtime(&self) -> &Runtime {
+        self.network.init_runtime()
+    }
 }
 
 impl ChannelSubclass for Plugin {
@@ -64,32 +84,411 @@ impl GstObjectImpl for Plugin {}
 impl BaseSinkImpl for Plugin {
     fn start(&self) -> Result<(), ErrorMessage> {
         BaseSinkImpl::unlock_stop(self)?;
-        self.runtime().block_on(async {
+        self.init_runtime().block_on(async {
+            let start_index = self.args().read().await.start_index();
+            self.stream.lock().await.reset(None, start_index);
+
             <Self as ChannelSubclassExt>::start(self).await?;
+
+            // nothing would preroll the element sink pad if only the request pads are in use
+            if !self.obj().sink_pad().is_linked() {
+                self.obj().set_async_enabled(false);
+            }
+
+            // the request pads may publish to the models instead
+            if self.args().read().await.model().is_empty() {
+                return Ok(());
+            }
             <Self as ChannelSubclassExt>::start_send(self).await
         })
     }
 
     fn stop(&self) -> Result<(), ErrorMessage> {
+        // publish the accumulated buffers first
+        if let Err(error) = self.runtime().block_on(self.flush_batch()) {
+            error!(
+                self.cat(),
+                imp: self,
+                "Failed to flush the batched buffers: {error}",
+            );
+        }
+
         BaseSinkImpl::unlock(self)?;
         self.runtime()
             .block_on(<Self as ChannelSubclassExt>::stop(self))
     }
 
+    #[inline]
+    fn unlock(&self) -> Result<(), ErrorMessage> {
+        self.channel().unlock();
+        Ok(())
+    }
+
+    #[inline]
+    fn unlock_stop(&self) -> Result<(), ErrorMessage> {
+        self.channel().unlock_stop();
+        Ok(())
+    }
+
+    fn event(&self, event: Event) -> bool {
+        match event.view() {
+            EventView::Eos(_) => {
+                // let the receivers terminate as well
+                match self.runtime().block_on(self.send_eos()) {
+                    Ok(()) | Err(FlowError::Flushing) => (),
+                    Err(error) => {
+                        error!(
+                            self.cat(),
+                            imp: self,
+                            "Failed to send an end-of-stream message: {error}",
+                        );
+                    }
+                }
+            }
+            EventView::StreamStart(event) => {
+                let stream_id = event.stream_id().to_string();
+                self.runtime().block_on(async {
+                    let mut stream = self.stream.lock().await;
+                    if stream.id() != Some(stream_id.as_str()) {
+                        let start_index = self.args().read().await.start_index();
+                        stream.reset(Some(stream_id), start_index);
+                    }
+                });
+            }
+            EventView::Tag(event) => {
+                let tags = event.tag();
+                debug!(
+                    self.cat(),
+                    imp: self,
+                    "Received tags {tags:?}",
+                );
+                self.stream.blocking_lock().set_tags(tags);
+            }
+            _ => (),
+        }
+        self.parent_event(event)
+    }
+
+    fn set_caps(&self, caps: &Caps) -> Result<(), LoggableError> {
+        debug!(
+            self.cat(),
+            imp: self,
+            "Negotiated caps {caps:?}",
+        );
+
+        self.caps.blocking_write().replace(caps.clone());
+        Ok(())
+    }
+
+    #[inline]
     fn render(&self, buffer: &Buffer) -> Result<FlowSuccess, FlowError> {
-        // get data index
-        let index = self.counter.fetch_add(1, Ordering::SeqCst);
+        self.runtime().block_on(self.render_buffer(buffer))
+    }
+
+    fn render_list(&self, list: &BufferList) -> Result<FlowSuccess, FlowError> {
+        self.runtime().block_on(async {
+            for buffer in list.iter_owned() {
+                self.render_buffer(&buffer).await?;
+            }
+            Ok(FlowSuccess::Ok)
+        })
+    }
+}
+
+impl Plugin {
+    pub(crate) fn activate_pad(
+        &self,
+        pad: &SinkPad,
+        mode: PadMode,
+        active: bool,
+    ) -> Result<(), LoggableError> {
+        if mode != PadMode::Push {
+            return Err(loggable_error!(self.cat(), "Unsupported pad mode {mode:?}"));
+        }
+
+        // the request pads may be activated before the element sink pad
+        self.init_runtime()
+            .block_on(async {
+                if active {
+                    let (model, start_index) = {
+                        let args = self.args().read().await;
+                        let model = pad.model().unwrap_or_else(|| args.model().clone());
+                        (model, args.start_index())
+                    };
+                    {
+                        let mut stream = pad.stream().lock().await;
+                        stream.reset(Some(pad.stream_id()), start_index);
+                        stream.sticky = true;
+                    }
+                    pad.set_eos(false);
+
+                    pad.channel().unlock_stop();
+                    self.start_channel(pad.channel(), model).await?;
+                    self.start_send_channel(pad.channel()).await
+                } else {
+                    // publish the accumulated buffers first
+                    if let Err(error) = self.flush_channel_batch(pad.channel()).await {
+                        error!(
+                            self.cat(),
+                            obj: pad,
+                            "Failed to flush the batched buffers: {error}",
+                        );
+                    }
+
+                    pad.channel().unlock();
+                    self.stop_channel(pad.channel()).await;
+                    Ok(())
+                }
+            })
+            .map_err(|error| loggable_error!(self.cat(), "{error:?}"))
+    }
+
+    pub(crate) fn chain_pad(
+        &self,
+        pad: &SinkPad,
+        buffer: Buffer,
+    ) -> Result<FlowSuccess, FlowError> {
+        self.runtime().block_on(self.render_pad(pad, &buffer))
+    }
+
+    pub(crate) fn chain_list_pad(
+        &self,
+        pad: &SinkPad,
+        list: BufferList,
+    ) -> Result<FlowSuccess, FlowError> {
+        self.runtime().block_on(async {
+            for buffer in list.iter_owned() {
+                self.render_pad(pad, &buffer).await?;
+            }
+            Ok(FlowSuccess::Ok)
+        })
+    }
+
+    pub(crate) fn event_pad(&self, pad: &SinkPad, event: Event) -> bool {
+        match event.view() {
+            EventView::Caps(event) => {
+                let caps = event.caps_owned();
+                debug!(
+                    self.cat(),
+                    obj: pad,
+                    "Negotiated caps {caps:?}",
+                );
+                pad.caps().blocking_write().replace(caps);
+            }
+            EventView::Eos(_) => {
+                // let the receivers terminate as well
+                match self
+                    .runtime()
+                    .block_on(self.send_channel_eos(pad.channel()))
+                {
+                    Ok(()) | Err(FlowError::Flushing) => (),
+                    Err(error) => {
+                        error!(
+                            self.cat(),
+                            obj: pad,
+                            "Failed to send an end-of-stream message: {error}",
+                        );
+                    }
+                }
+                if pad.set_eos(true) {
+                    self.check_eos();
+                }
+            }
+            EventView::FlushStart(_) => pad.channel().unlock(),
+            EventView::FlushStop(_) => {
+                pad.channel().unlock_stop();
+                pad.set_eos(false);
+            }
+            EventView::Segment(event) => match event.segment().downcast_ref::<ClockTime>() {
+                Some(segment) => pad.set_segment(segment.clone()),
+                None => {
+                    error!(
+                        self.cat(),
+                        obj: pad,
+                        "Unsupported segment {segment:?}",
+                        segment = event.segment(),
+                    );
+                    return false;
+                }
+            },
+            EventView::Tag(event) => {
+                let tags = event.tag();
+                debug!(
+                    self.cat(),
+                    obj: pad,
+                    "Received tags {tags:?}",
+                );
+                pad.stream().blocking_lock().set_tags(tags);
+            }
+            _ => (),
+        }
+        true
+    }
+
+    pub(crate) fn spawn_switch_pad_model(&self, pad: &SinkPad) {
+        // the model is picked up on activating the pad
+        if !pad.channel().is_started() {
+            return;
+        }
+
+        let element = self.obj().downgrade();
+        let pad = pad.clone();
+        self.runtime().spawn(async move {
+            let element = match element.upgrade() {
+                Some(element) => element,
+                None => return,
+            };
+            let imp = element.imp();
+            let model = match pad.model() {
+                Some(model) => model,
+                None => imp.args().read().await.model().clone(),
+            };
+            imp.switch_channel_model(pad.channel(), model).await
+        });
+    }
+
+    /// Posts an end-of-stream message once all the request pads are ended,
+    /// unless the element sink pad is in use.
+    fn check_eos(&self) {
+        let element = self.obj();
+        if element.sink_pad().is_linked() {
+            return;
+        }
+
+        let all_eos = element
+            .sink_pads()
+            .into_iter()
+            .filter_map(|pad| pad.downcast::<SinkPad>().ok())
+            .all(|pad| pad.is_eos());
+        if all_eos {
+            debug!(
+                self.cat(),
+                imp: self,
+                "All request pads are ended",
+            );
+            let message = message::Eos::builder().src(&*element).build();
+            // the element may not be in a bin
+            let _ = element.post_message(message);
+        }
+    }
+
+    async fn render_buffer(&self, buffer: &Buffer) -> Result<FlowSuccess, FlowError> {
+        let running_time = self
+            .obj()
+            .segment()
+            .downcast_ref::<ClockTime>()
+            .and_then(|segment| segment.to_running_time(buffer.pts()));
+
+        self.render_to(
+            self.channel(),
+            &self.caps,
+            &self.stream,
+            None,
+            running_time,
+            buffer,
+        )
+        .await
+    }
+
+    /// Render the buffer of a request pad, keyed under `<element-name>/<pad-name>`.
+    ///
+    /// Without `{element-name}` in the key template, the keys are prefixed with the pad name.
+    async fn render_pad(&self, pad: &SinkPad, buffer: &Buffer) -> Result<FlowSuccess, FlowError> {
+        let running_time = pad.running_time(buffer.pts());
+
+        self.render_to(
+            pad.channel(),
+            pad.caps(),
+            pad.stream(),
+            Some(&pad.name()),
+            running_time,
+            buffer,
+        )
+        .await
+    }
+
+    async fn render_to(
+        &self,
+        channel: &Channel,
+        caps: &RwLock<Option<Caps>>,
+        stream: &Mutex<Stream>,
+        pad_name: Option<&str>,
+        running_time: Option<ClockTime>,
+        buffer: &Buffer,
+    ) -> Result<FlowSuccess, FlowError> {
+        let element_name = self.obj().name();
+        let caps = caps.read().await;
 
-        // TODO: handle other media types (audio, JSON, plain, ...)
-        // TODO: support non-image(video) data using sink Caps and cache it
         // parse data extension
-        let ext = ".jpg";
+        let ext = caps
+            .as_deref()
+            .map(MediaType::from_caps)
+            .unwrap_or(&MediaType::FALLBACK)
+            .ext;
+
+        // get data index
+        let mut stream = stream.lock().await;
+        let index = stream.next_index();
+        let info = stream.take_changed();
 
-        // TODO: handle other media types (audio, JSON, plain, ...)
-        // TODO: support non-image(video) data using sink Caps and cache it
         // build a payload
-        let key = format!("{index:06}{ext}");
+        let key = self.args().read().await.key_template().render(&KeyContext {
+            dts: buffer.dts(),
+            element_name: &element_name,
+            ext,
+            index,
+            pad_name,
+            pts: buffer.pts(),
+            running_time,
+            stream_id: stream.id(),
+        });
+        drop(stream);
+
+        self.send_channel_buffer(channel, key, caps.as_deref(), info.as_ref(), buffer)
+            .await
+    }
+}
+
+/// Per-stream state of the payload keys
+#[derive(Default)]
+pub(crate) struct Stream {
+    changed: bool,
+    counter: u64,
+    info: StreamInfo,
+    /// Whether to send the stream info with every message
+    sticky: bool,
+}
+
+impl Stream {
+    fn reset(&mut self, id: Option<String>, start_index: u64) {
+        self.counter = start_index;
+
+        // the global tags outlive the stream
+        self.info = StreamInfo {
+            stream_id: id,
+            tags: None,
+            global_tags: self.info.global_tags.take(),
+        };
+        self.changed = true;
+    }
+
+    fn id(&self) -> Option<&str> {
+        self.info.stream_id.as_deref()
+    }
+
+    fn set_tags(&mut self, tags: &TagListRef) {
+        self.info.set_tags(tags);
+        self.changed = true;
+    }
+
+    /// Returns the stream info to be sent, if changed since the last call.
+    fn take_changed(&mut self) -> Option<StreamInfo> {
+        (::core::mem::take(&mut self.changed) || self.sticky).then(|| self.info.clone())
+    }
 
-        self.runtime().block_on(self.send_buffer(key, buffer))
+    fn next_index(&mut self) -> u64 {
+        let index = self.counter;
+        self.counter += 1;
+        index
     }
 }
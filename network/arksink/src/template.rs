@@ -0,0 +1,272 @@
+use std::{
+    fmt,
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{anyhow, bail, Error, Result};
+use gst::ClockTime;
+
+/// Payload key template, e.g. `{element-name}/{index:06}{ext}`
+#[derive(Clone, PartialEq, Eq)]
+pub struct KeyTemplate {
+    source: String,
+    segments: Vec<Segment>,
+}
+
+impl Default for KeyTemplate {
+    #[inline]
+    fn default() -> Self {
+        Self::DEFAULT
+            .parse()
+            .expect("default key template should be valid")
+    }
+}
+
+impl FromStr for KeyTemplate {
+    type Err = Error;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        let mut segments = vec![];
+        let mut literal = String::default();
+
+        let mut chars = source.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.as_str().starts_with('{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.as_str().starts_with('}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let rest = chars.as_str();
+                    let end = rest
+                        .find('}')
+                        .ok_or_else(|| anyhow!("unclosed placeholder in {source:?}"))?;
+                    let placeholder = &rest[..end];
+                    chars = rest[end + 1..].chars();
+
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(::core::mem::take(&mut literal)));
+                    }
+                    segments.push(placeholder.parse()?);
+                }
+                '}' => bail!("unmatched '}}' in {source:?}"),
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+
+        if segments.is_empty() {
+            bail!("empty key template");
+        }
+        Ok(Self {
+            source: source.into(),
+            segments,
+        })
+    }
+}
+
+impl fmt::Debug for KeyTemplate {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.source.fmt(f)
+    }
+}
+
+impl fmt::Display for KeyTemplate {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.source.fmt(f)
+    }
+}
+
+impl KeyTemplate {
+    pub const DEFAULT: &'static str = "{index:06}{ext}";
+
+    /// Render the key of a buffer.
+    ///
+    /// With the default template, the keys of a request pad are prefixed with the pad name,
+    /// so that the pads sharing a model never collide.
+    pub fn render(&self, ctx: &KeyContext<'_>) -> String {
+        let key: String = self
+            .segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Literal(literal) => literal.clone(),
+                Segment::Placeholder { kind, width } => kind.render(ctx, *width),
+            })
+            .collect();
+
+        match ctx.pad_name {
+            Some(pad_name) if self.source == Self::DEFAULT => format!("{pad_name}/{key}"),
+            _ => key,
+        }
+    }
+}
+
+/// Per-buffer values to be filled into a [`KeyTemplate`]
+pub struct KeyContext<'a> {
+    pub dts: Option<ClockTime>,
+    pub element_name: &'a str,
+    pub ext: &'a str,
+    pub index: u64,
+    /// Name of the request pad, if not the element sink pad
+    pub pad_name: Option<&'a str>,
+    pub pts: Option<ClockTime>,
+    pub running_time: Option<ClockTime>,
+    pub stream_id: Option<&'a str>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Placeholder {
+        kind: Placeholder,
+        width: Option<usize>,
+    },
+}
+
+impl FromStr for Segment {
+    type Err = Error;
+
+    fn from_str(placeholder: &str) -> Result<Self, Self::Err> {
+        let (name, width) = match placeholder.split_once(':') {
+            Some((name, spec)) => {
+                let width = spec
+                    .strip_prefix('0')
+                    .and_then(|width| width.parse().ok())
+                    .ok_or_else(|| anyhow!("invalid width {spec:?} of {{{name}}}"))?;
+                (name, Some(width))
+            }
+            None => (placeholder, None),
+        };
+
+        let kind = match name {
+            "dts" => Placeholder::Dts,
+            "element-name" => Placeholder::ElementName,
+            "ext" => Placeholder::Ext,
+            "index" => Placeholder::Index,
+            "pad-name" => Placeholder::PadName,
+            "pts" => Placeholder::Pts,
+            "running-time" => Placeholder::RunningTime,
+            "stream-id" => Placeholder::StreamId,
+            "uuid" => Placeholder::Uuid,
+            "wallclock" => Placeholder::Wallclock,
+            name => bail!("unknown placeholder {{{name}}}"),
+        };
+
+        if width.is_some() && !kind.is_numeric() {
+            bail!("{{{name}}} cannot be zero-padded");
+        }
+        Ok(Self::Placeholder { kind, width })
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Placeholder {
+    Dts,
+    ElementName,
+    Ext,
+    Index,
+    PadName,
+    Pts,
+    RunningTime,
+    StreamId,
+    Uuid,
+    Wallclock,
+}
+
+impl Placeholder {
+    const fn is_numeric(&self) -> bool {
+        matches!(
+            self,
+            Self::Dts | Self::Index | Self::Pts | Self::RunningTime | Self::Wallclock
+        )
+    }
+
+    fn render(&self, ctx: &KeyContext<'_>, width: Option<usize>) -> String {
+        let number = match self {
+            Self::Dts => ctx.dts.map(ClockTime::nseconds),
+            Self::ElementName => return ctx.element_name.into(),
+            Self::Ext => return ctx.ext.into(),
+            Self::Index => Some(ctx.index),
+            // the element sink pad
+            Self::PadName => return ctx.pad_name.unwrap_or("sink").into(),
+            Self::Pts => ctx.pts.map(ClockTime::nseconds),
+            Self::RunningTime => ctx.running_time.map(ClockTime::nseconds),
+            Self::StreamId => return ctx.stream_id.unwrap_or("none").into(),
+            Self::Uuid => return ::uuid::Uuid::new_v4().to_string(),
+            Self::Wallclock => SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .ok()
+                .map(|time| time.as_nanos() as u64),
+        };
+
+        match (number, width) {
+            (Some(number), Some(width)) => format!("{number:0width$}"),
+            (Some(number), None) => number.to_string(),
+            (None, _) => "none".into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render_pads(template: &str) -> Vec<String> {
+        let template: KeyTemplate = template.parse().unwrap();
+        ["sink_0", "sink_1"]
+            .into_iter()
+            .flat_map(|pad_name| {
+                let template = &template;
+                (0..2).map(move |index| {
+                    template.render(&KeyContext {
+                        dts: None,
+                        element_name: "sink",
+                        ext: ".jpg",
+                        index,
+                        pad_name: Some(pad_name),
+                        pts: None,
+                        running_time: None,
+                        stream_id: Some(pad_name),
+                    })
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn request_pads_sharing_a_model_have_distinct_keys() {
+        for template in [
+            KeyTemplate::DEFAULT,
+            "{element-name}/{pad-name}/{index:06}{ext}",
+        ] {
+            let keys = render_pads(template);
+            let mut unique = keys.clone();
+            unique.sort();
+            unique.dedup();
+            assert_eq!(keys.len(), unique.len(), "{template}: {keys:?}");
+        }
+    }
+
+    #[test]
+    fn request_pads_are_prefixed_by_the_default_template_only() {
+        assert_eq!(render_pads(KeyTemplate::DEFAULT)[1], "sink_0/000001.jpg");
+        assert_eq!(
+            render_pads("{element-name}/{pad-name}/{index:06}{ext}")[3],
+            "sink/sink_1/000001.jpg",
+        );
+        assert_eq!(
+            render_pads("{element-name}/{index:06}{ext}")[3],
+            "sink/000001.jpg"
+        );
+        assert_eq!(render_pads("{pts}")[3], "none");
+    }
+}
@@ -1,8 +1,192 @@
-use gsark_common::{args::Args, net::ChannelArgs, plugin::base::ArkSubclass};
-use gst::glib::{subclass::object::ObjectImpl, ParamSpec, Value};
+use glob::Pattern;
+use gsark_common::{
+    args::{ClientArgs, Params, QueueLeaky, QueueLimits, ReconnectArgs, StatsArgs},
+    net::{ChannelArgs, ChannelSubclass, ChannelSubclassExt},
+    payload::PayloadMode,
+    plugin::{base::ArkSubclass, PluginImpl},
+    value::{set_value, try_set_value},
+};
+use gst::{
+    glib::{
+        subclass::{
+            object::{ObjectImpl, ObjectImplExt},
+            types::ObjectSubclassExt,
+        },
+        value::ToValue,
+        ParamSpec, ParamSpecBoolean, ParamSpecBoxed, ParamSpecBuilderExt, ParamSpecEnum,
+        ParamSpecString, Value,
+    },
+    Caps, Format,
+};
+use gst_base::prelude::BaseSrcExt;
+
+/// Plugin property value storage
+#[derive(Clone, Debug)]
+pub struct Args {
+    caps: Option<Caps>,
+    common: ::gsark_common::args::Args,
+    ignore_eos: bool,
+    payload_key: Option<Pattern>,
+    payload_mode: PayloadMode,
+    queue: QueueLimits,
+    reconnect: ReconnectArgs,
+    stats: StatsArgs,
+    rebase_timestamps: bool,
+}
+
+impl Default for Args {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            caps: Default::default(),
+            common: Default::default(),
+            ignore_eos: Default::default(),
+            payload_key: Default::default(),
+            payload_mode: Default::default(),
+            // keep up with the live stream, dropping the new messages if the pipeline falls behind
+            queue: QueueLimits {
+                leaky: QueueLeaky::Upstream,
+                ..Default::default()
+            },
+            reconnect: Default::default(),
+            stats: Default::default(),
+            // the remote timestamps are far ahead of a pipeline joining late
+            rebase_timestamps: true,
+        }
+    }
+}
+
+impl ChannelArgs for Args {
+    #[inline]
+    fn model(&self) -> &String {
+        self.common.model()
+    }
+
+    #[inline]
+    fn client(&self) -> &ClientArgs {
+        self.common.client()
+    }
+
+    #[inline]
+    fn dedicated_runtime(&self) -> bool {
+        self.common.dedicated_runtime()
+    }
+
+    #[inline]
+    fn otlp(&self) -> bool {
+        self.common.otlp()
+    }
+
+    #[inline]
+    fn stats_interval(&self) -> u32 {
+        self.stats.interval
+    }
+
+    #[inline]
+    fn caps(&self) -> Option<Caps> {
+        self.caps.clone()
+    }
+
+    #[inline]
+    fn ignore_eos(&self) -> bool {
+        self.ignore_eos
+    }
+
+    #[inline]
+    fn payload_key(&self) -> Option<&Pattern> {
+        self.payload_key.as_ref()
+    }
+
+    #[inline]
+    fn payload_mode(&self) -> PayloadMode {
+        self.payload_mode
+    }
+
+    #[inline]
+    fn rebase_timestamps(&self) -> bool {
+        self.rebase_timestamps
+    }
+
+    #[inline]
+    fn queue(&self) -> QueueLimits {
+        self.queue.clone()
+    }
+
+    #[inline]
+    fn reconnect(&self) -> ReconnectArgs {
+        self.reconnect.clone()
+    }
+
+    #[inline]
+    fn properties() -> &'static [ParamSpec] {
+        static PROPERTIES: Params = Params::new(|| Args::default().as_params());
+
+        PROPERTIES.as_ref()
+    }
+
+    #[inline]
+    fn property(&self, id: usize, pspec: &ParamSpec) -> Value {
+        match pspec.name() {
+            "caps" => self.caps.to_value(),
+            "ignore-eos" => self.ignore_eos.to_value(),
+            "payload-key" => self
+                .payload_key
+                .as_ref()
+                .map(|pattern| pattern.as_str())
+                .to_value(),
+            "payload-mode" => self.payload_mode.to_value(),
+            "rebase-timestamps" => self.rebase_timestamps.to_value(),
+            name if QueueLimits::is_property(name) => self.queue.property(id, pspec),
+            name if ReconnectArgs::is_property(name) => self.reconnect.property(id, pspec),
+            name if StatsArgs::is_property(name) => self.stats.property(id, pspec),
+            _ => self.common.property(id, pspec),
+        }
+    }
+
+    #[inline]
+    fn set_property(
+        &mut self,
+        plugin: &(impl ?Sized + PluginImpl),
+        id: usize,
+        value: &Value,
+        pspec: &ParamSpec,
+    ) {
+        let name = pspec.name();
+        match name {
+            "caps" => set_value(plugin, name, &mut self.caps, value),
+            "ignore-eos" => set_value(plugin, name, &mut self.ignore_eos, value),
+            "payload-key" => try_set_value(
+                plugin,
+                name,
+                &mut self.payload_key,
+                value,
+                |value: Option<String>| value.as_deref().map(Pattern::new).transpose(),
+            ),
+            "payload-mode" => set_value(plugin, name, &mut self.payload_mode, value),
+            "rebase-timestamps" => set_value(plugin, name, &mut self.rebase_timestamps, value),
+            name if QueueLimits::is_property(name) => {
+                self.queue.set_property(plugin, id, value, pspec)
+            }
+            name if ReconnectArgs::is_property(name) => {
+                self.reconnect.set_property(plugin, id, value, pspec)
+            }
+            name if StatsArgs::is_property(name) => {
+                self.stats.set_property(plugin, id, value, pspec)
+            }
+            _ => self.common.set_property(plugin, id, value, pspec),
+        }
+    }
+}
 
 /// Implementation of glib::Object virtual methods
 impl ObjectImpl for crate::plugin::Plugin {
+    fn constructed(&self) {
+        self.parent_constructed();
+
+        // the received buffers are timestamped
+        self.obj().set_format(Format::Time);
+    }
+
     #[inline]
     fn properties() -> &'static [ParamSpec] {
         Args::properties()
@@ -12,11 +196,67 @@ impl ObjectImpl for crate::plugin::Plugin {
     fn set_property(&self, id: usize, value: &Value, pspec: &ParamSpec) {
         self.args()
             .blocking_write()
-            .set_property(self, id, value, pspec)
+            .set_property(self, id, value, pspec);
+
+        // the model can be switched while running
+        if pspec.name() == "model" {
+            self.spawn_switch_model();
+        }
     }
 
     #[inline]
     fn property(&self, id: usize, pspec: &ParamSpec) -> Value {
-        self.args().blocking_read().property(id, pspec)
+        match pspec.name() {
+            "stats" => self.channel().stats().to_structure().to_value(),
+            _ => self.args().blocking_read().property(id, pspec),
+        }
+    }
+}
+
+impl Args {
+    fn as_params(&self) -> Vec<ParamSpec> {
+        let mut params = self.common.as_params();
+        params.extend(self.queue.as_params());
+        params.extend(self.reconnect.as_params());
+        params.extend(self.stats.as_params());
+        params.push(
+            ParamSpecBoxed::builder::<Caps>("caps")
+                .nick("Caps")
+                .blurb(
+                    "Caps of the received payloads, overriding the ones inferred from the messages",
+                )
+                .build(),
+        );
+        params.push(
+            ParamSpecBoolean::builder("ignore-eos")
+                .nick("Ignore EOS")
+                .blurb("Whether to ignore the end-of-stream messages, e.g. to chain producers")
+                .default_value(self.ignore_eos)
+                .build(),
+        );
+        params.push(
+            ParamSpecString::builder("payload-key")
+                .nick("Payload Key")
+                .blurb("Glob pattern of the payload keys to be received (e.g. \"*.jpg\")")
+                .default_value(None)
+                .build(),
+        );
+        params.push(
+            ParamSpecEnum::builder_with_default("payload-mode", self.payload_mode)
+                .nick("Payload Mode")
+                .blurb("How to produce the selected payloads of each message")
+                .build(),
+        );
+        params.push(
+            ParamSpecBoolean::builder("rebase-timestamps")
+                .nick("Rebase Timestamps")
+                .blurb(
+                    "Whether to rebase the received timestamps onto the running time, \
+                    otherwise the remote ones are kept as is",
+                )
+                .default_value(self.rebase_timestamps)
+                .build(),
+        );
+        params
     }
 }
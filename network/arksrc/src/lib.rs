@@ -1,5 +1,6 @@
 mod args;
 mod element;
+mod pad;
 mod plugin;
 
 use gst::{glib, prelude::StaticType, DebugCategory, DebugColorFlags, Element, Object, Rank};
@@ -30,6 +31,8 @@ glib::wrapper! {
 /// the name for being able to instantiate it via e.g.
 /// gst::ElementFactory::make().
 pub fn register(plugin: &gst::Plugin) -> Result<(), glib::BoolError> {
+    ::gsark_common::meta::ArkMessageMeta::register();
+
     Element::register(
         Some(plugin),
         crate::metadata::NAME,
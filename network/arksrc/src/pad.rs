@@ -0,0 +1,110 @@
+use gsark_common::net::Channel;
+use gst::{
+    glib::{self, subclass::types::ObjectSubclassIsExt},
+    prelude::GstObjectExt,
+};
+
+// The public Rust wrapper type for our request pads
+glib::wrapper! {
+    pub struct SrcPad(ObjectSubclass<imp::SrcPad>)
+    @extends
+        gst::Pad,
+        gst::Object
+    ;
+}
+
+impl SrcPad {
+    /// OpenARK model name, defaulting to the pad name without the `src_` prefix
+    pub fn model(&self) -> String {
+        match self.imp().model.lock().unwrap().clone() {
+            Some(model) => model,
+            None => {
+                let name = self.name();
+                name.strip_prefix("src_").unwrap_or(&name).to_string()
+            }
+        }
+    }
+
+    #[inline]
+    pub fn channel(&self) -> &Channel {
+        &self.imp().channel
+    }
+}
+
+mod imp {
+    use std::sync::Mutex;
+
+    use gsark_common::net::Channel;
+    use gst::{
+        glib::{
+            self,
+            subclass::{
+                object::ObjectImpl,
+                types::{ObjectSubclass, ObjectSubclassIsExt},
+            },
+            value::ToValue,
+            ParamSpec, ParamSpecBuilderExt, ParamSpecString, Value,
+        },
+        prelude::{CastNone, GstObjectExt},
+        subclass::prelude::{GstObjectImpl, ObjectSubclassExt, PadImpl},
+    };
+    use once_cell::sync::Lazy;
+
+    /// Struct containing all the request pad data
+    #[derive(Default)]
+    pub struct SrcPad {
+        pub(super) channel: Channel,
+        pub(super) model: Mutex<Option<String>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for SrcPad {
+        const NAME: &'static str = "ArkSrcPad";
+        type Type = super::SrcPad;
+        type ParentType = gst::Pad;
+    }
+
+    impl ObjectImpl for SrcPad {
+        fn properties() -> &'static [ParamSpec] {
+            static PROPERTIES: Lazy<Vec<ParamSpec>> = Lazy::new(|| {
+                vec![ParamSpecString::builder("model")
+                    .nick("Model")
+                    .blurb("OpenARK model name, defaulting to the pad name without the prefix")
+                    .default_value(None)
+                    .build()]
+            });
+
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &Value, pspec: &ParamSpec) {
+            match pspec.name() {
+                "model" => {
+                    let model = value
+                        .get::<Option<String>>()
+                        .expect("type checked upstream")
+                        .filter(|model| !model.is_empty());
+                    *self.model.lock().unwrap() = model;
+
+                    // the model can be switched while running
+                    let pad = self.obj();
+                    if let Some(element) = pad.parent().and_downcast::<crate::Plugin>() {
+                        element.imp().spawn_switch_pad_model(&pad);
+                    }
+                }
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &ParamSpec) -> Value {
+            match pspec.name() {
+                "model" => self.obj().model().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+    }
+
+    impl GstObjectImpl for SrcPad {}
+
+    impl PadImpl for SrcPad {}
+}
@@ -1,20 +1,35 @@
+use std::future::pending;
+
 use anyhow::Result;
 use gsark_common::{
-    args::Args,
-    net::{Channel, ChannelSubclass, ChannelSubclassExt},
+    net::{take_buffer_list, Channel, ChannelSubclass, ChannelSubclassExt},
+    payload::PayloadMode,
     plugin::{base::ArkSubclass, network::NetworkPlugin, PluginImpl},
 };
 use gst::{
-    glib::{self, subclass::types::ObjectSubclass},
+    debug, element_imp_error, event,
+    glib::{
+        self,
+        subclass::types::{ObjectSubclass, ObjectSubclassExt, ObjectSubclassIsExt},
+    },
+    loggable_error,
+    prelude::{Cast, CastNone, GstObjectExt, ObjectExt, PadExt, PadExtManual},
+    query,
     subclass::prelude::GstObjectImpl,
-    BufferRef, DebugCategory, ErrorMessage,
+    BufferPool, BufferRef, Caps, ClockTime, DebugCategory, ErrorMessage, FlowError, FlowSuccess,
+    FormattedSegment, LoggableError, PadMode, QueryRef, QueryViewMut, StreamError,
 };
-use gst_base::subclass::{
-    base_src::{BaseSrcImpl, CreateSuccess},
-    prelude::PushSrcImpl,
+use gst_base::{
+    prelude::BaseSrcExt,
+    subclass::{
+        base_src::{BaseSrcImpl, BaseSrcImplExt, CreateSuccess},
+        prelude::PushSrcImpl,
+    },
 };
 use tokio::{runtime::Runtime, sync::RwLock};
 
+use crate::{args::Args, pad::SrcPad};
+
 /// Struct containing all the element data
 #[derive(Default)]
 pub struct Plugin {
@@ -50,6 +65,11 @@ impl ArkSubclass for Plugin {
     fn runtime(&self) -> &Runtime {
         self.network.runtime()
     }
+
+    #[inline]
+    fn init_runtime(&self) -> &Runtime {
+        self.network.init_runtime()
+    }
 }
 
 impl ChannelSubclass for Plugin {
@@ -65,8 +85,13 @@ impl BaseSrcImpl for Plugin {
     #[inline]
     fn start(&self) -> Result<(), ErrorMessage> {
         BaseSrcImpl::unlock_stop(self)?;
-        self.runtime().block_on(async {
+        self.init_runtime().block_on(async {
             <Self as ChannelSubclassExt>::start(self).await?;
+
+            // the request pads may subscribe to the models instead
+            if self.args().read().await.model().is_empty() {
+                return Ok(());
+            }
             <Self as ChannelSubclassExt>::start_recv(self).await
         })
     }
@@ -78,6 +103,33 @@ impl BaseSrcImpl for Plugin {
             .block_on(<Self as ChannelSubclassExt>::stop(self))
     }
 
+    #[inline]
+    fn unlock(&self) -> Result<(), ErrorMessage> {
+        self.channel().unlock();
+        Ok(())
+    }
+
+    #[inline]
+    fn unlock_stop(&self) -> Result<(), ErrorMessage> {
+        self.channel().unlock_stop();
+        Ok(())
+    }
+
+    fn decide_allocation(&self, query: &mut query::Allocation) -> Result<(), LoggableError> {
+        // propose buffers fitting the recent payloads
+        let size = self.channel().size_hint() as u32;
+        if size > 0 {
+            match query.allocation_pools().into_iter().next() {
+                Some((pool, pool_size, min, max)) => {
+                    query.set_nth_allocation_pool(0, pool.as_ref(), size.max(pool_size), min, max)
+                }
+                None => query.add_allocation_pool(None::<&BufferPool>, size, 0, 0),
+            }
+        }
+
+        self.parent_decide_allocation(query)
+    }
+
     #[inline]
     fn is_seekable(&self) -> bool {
         false
@@ -91,6 +143,161 @@ impl BaseSrcImpl for Plugin {
 
 impl PushSrcImpl for Plugin {
     fn create(&self, buffer: Option<&mut BufferRef>) -> Result<CreateSuccess, gst::FlowError> {
-        self.runtime().block_on(self.recv_buffer(buffer))
+        self.runtime().block_on(async {
+            // the request pads may subscribe to the models instead
+            if self.args().read().await.model().is_empty() {
+                return self.channel().cancellable(pending()).await;
+            }
+            self.recv_buffer(buffer).await
+        })
+    }
+}
+
+impl Plugin {
+    pub(crate) fn activate_pad(
+        &self,
+        pad: &SrcPad,
+        mode: PadMode,
+        active: bool,
+    ) -> Result<(), LoggableError> {
+        if mode != PadMode::Push {
+            return Err(loggable_error!(self.cat(), "Unsupported pad mode {mode:?}"));
+        }
+
+        if active {
+            let model = pad.model();
+            // the request pads may be activated before the element src pad
+            self.init_runtime()
+                .block_on(async {
+                    self.start_channel(pad.channel(), model).await?;
+                    self.start_recv_channel(pad.channel()).await
+                })
+                .map_err(|error| loggable_error!(self.cat(), "{error:?}"))?;
+            pad.channel().unlock_stop();
+
+            let weak = pad.downgrade();
+            pad.start_task(move || {
+                let pad = match weak.upgrade() {
+                    Some(pad) => pad,
+                    None => return,
+                };
+                match pad.parent().and_downcast::<super::Plugin>() {
+                    Some(element) => element.imp().loop_pad(&pad),
+                    None => {
+                        let _ = pad.pause_task();
+                    }
+                }
+            })
+            .map_err(|error| loggable_error!(self.cat(), "{error}"))
+        } else {
+            pad.channel().unlock();
+            pad.stop_task()
+                .map_err(|error| loggable_error!(self.cat(), "{error}"))?;
+            self.runtime().block_on(self.stop_channel(pad.channel()));
+            Ok(())
+        }
+    }
+
+    pub(crate) fn query_pad(&self, pad: &SrcPad, query: &mut QueryRef) -> bool {
+        match query.view_mut() {
+            // the messages are pushed as soon as they are received, as the element src pad does
+            QueryViewMut::Latency(query) => {
+                query.set(self.obj().is_live(), ClockTime::ZERO, ClockTime::NONE);
+                true
+            }
+            _ => pad.query_default(Some(&*self.obj()), query),
+        }
+    }
+
+    pub(crate) fn spawn_switch_pad_model(&self, pad: &SrcPad) {
+        // the model is picked up on activating the pad
+        if !pad.channel().is_started() {
+            return;
+        }
+
+        let element = self.obj().downgrade();
+        let pad = pad.clone();
+        self.runtime().spawn(async move {
+            if let Some(element) = element.upgrade() {
+                let model = pad.model();
+                element
+                    .imp()
+                    .switch_channel_model(pad.channel(), model)
+                    .await
+            }
+        });
+    }
+
+    fn loop_pad(&self, pad: &SrcPad) {
+        let error = match self.runtime().block_on(self.push_pad(pad)) {
+            Ok(_) => return,
+            Err(error) => error,
+        };
+
+        match error {
+            FlowError::Flushing => {
+                debug!(
+                    self.cat(),
+                    obj: pad,
+                    "Pausing after flushing",
+                );
+            }
+            FlowError::Eos => {
+                debug!(
+                    self.cat(),
+                    obj: pad,
+                    "Pausing after end-of-stream",
+                );
+                pad.push_event(event::Eos::new());
+            }
+            error => {
+                element_imp_error!(
+                    self,
+                    StreamError::Failed,
+                    ["Internal data flow error on {}: {error}", pad.name()]
+                );
+                pad.push_event(event::Eos::new());
+            }
+        }
+        let _ = pad.pause_task();
+    }
+
+    async fn push_pad(&self, pad: &SrcPad) -> Result<FlowSuccess, FlowError> {
+        // start the stream first
+        if pad.sticky_event::<event::StreamStart>(0).is_none() {
+            let stream_id = pad.create_stream_id(&*self.obj(), Some(pad.name().as_str()));
+            pad.push_event(event::StreamStart::new(&stream_id));
+        }
+
+        let (payload_mode, mut buffers) =
+            self.recv_buffers(pad.channel(), pad.upcast_ref()).await?;
+        match payload_mode {
+            PayloadMode::Buffers | PayloadMode::Last => {
+                for (buffer, caps) in buffers {
+                    Self::negotiate_pad(pad, caps.as_ref());
+                    pad.push(buffer)?;
+                }
+            }
+            // split the list wherever the caps change
+            PayloadMode::List => {
+                while !buffers.is_empty() {
+                    let (list, caps) = take_buffer_list(&mut buffers);
+                    Self::negotiate_pad(pad, caps.as_ref());
+                    pad.push_list(list)?;
+                }
+            }
+        }
+        Ok(FlowSuccess::Ok)
+    }
+
+    /// Send the caps and segment of each pad independently, before the data.
+    fn negotiate_pad(pad: &SrcPad, caps: Option<&Caps>) {
+        if let Some(caps) = caps.filter(|&caps| pad.current_caps().as_ref() != Some(caps)) {
+            pad.push_event(event::Caps::new(caps));
+        }
+        if pad.sticky_event::<event::Segment>(0).is_none() {
+            let segment = FormattedSegment::<ClockTime>::new();
+            pad.push_event(event::Segment::new(&segment));
+        }
     }
 }
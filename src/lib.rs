@@ -9,6 +9,7 @@ fn plugin_init(plugin: &gst::Plugin) -> Result<(), glib::BoolError> {
     }
     // network
     {
+        ::arkdemux::register(plugin)?;
         ::arksink::register(plugin)?;
         ::arksrc::register(plugin)?;
     }